@@ -0,0 +1,352 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::core::ast::Stmt;
+use crate::core::diagnostics::{self, Diagnostic};
+use crate::core::interpreter::{Interpreter, Value};
+use crate::core::lexer::Lexer;
+use crate::core::messages::MessageCatalog;
+use crate::core::parser::Parser;
+use crate::core::token::{Token, TokenData};
+use crate::core::type_checker::{TypeChecker, TypeError};
+use crate::persistence::models::SyntaxConfig;
+
+const PROMPT: &str = ">>> ";
+const CONTINUATION: &str = "... ";
+const SOURCE_NAME: &str = "<repl>";
+
+// Archivo de historial en el directorio actual; FABRICLANG_HISTORY lo cambia.
+// Se carga al iniciar para consultarlo con `:historial` y `:repetir`
+pub const HISTORY_FILE: &str = ".fabriclang_history";
+
+pub fn history_path() -> PathBuf {
+    std::env::var_os("FABRICLANG_HISTORY")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(HISTORY_FILE))
+}
+
+pub struct Repl {
+    config: SyntaxConfig,
+    interpreter: Interpreter,
+    // Conoce lo declarado en entradas anteriores que pasaron la verificación
+    checker: TypeChecker,
+    max_call_depth: usize,
+    history: Option<PathBuf>,
+    entries: Vec<String>,
+    messages: MessageCatalog,
+    color: bool,
+}
+
+impl Repl {
+    pub fn new(config: SyntaxConfig, max_call_depth: usize, history: Option<PathBuf>) -> Self {
+        let interpreter = Self::fresh_interpreter(&config, max_call_depth);
+        let mut repl = Self {
+            config,
+            interpreter,
+            checker: TypeChecker::new(),
+            max_call_depth,
+            history,
+            entries: Vec::new(),
+            messages: MessageCatalog::default(),
+            color: diagnostics::use_color(),
+        };
+        repl.entries = repl.load_history();
+        repl
+    }
+
+    pub fn set_messages(&mut self, messages: MessageCatalog) {
+        self.messages = messages;
+    }
+
+    fn fresh_interpreter(config: &SyntaxConfig, max_call_depth: usize) -> Interpreter {
+        let mut interpreter = Interpreter::new(config.clone());
+        interpreter.set_max_call_depth(max_call_depth);
+        interpreter
+    }
+
+    // Acumula líneas hasta que la entrada queda completa y entonces la ejecuta;
+    // el estado del intérprete se conserva entre entradas
+    pub fn run(&mut self, input: impl BufRead) -> io::Result<()> {
+        let mut lines = input.lines();
+        let mut buffer = String::new();
+        loop {
+            print!(
+                "{}",
+                if buffer.is_empty() {
+                    PROMPT
+                } else {
+                    CONTINUATION
+                }
+            );
+            io::stdout().flush()?;
+            let Some(line) = lines.next() else {
+                println!();
+                return Ok(());
+            };
+            let line = line?;
+
+            if buffer.is_empty() && line.trim_start().starts_with(':') {
+                self.record(line.trim())?;
+                if !self.command(line.trim()) {
+                    return Ok(());
+                }
+                continue;
+            }
+
+            buffer.push_str(&line);
+            buffer.push('\n');
+            if self.is_incomplete(&buffer) {
+                continue;
+            }
+            let entry = std::mem::take(&mut buffer);
+            if entry.trim().is_empty() {
+                continue;
+            }
+            self.record(entry.trim_end())?;
+            self.eval(&entry);
+        }
+    }
+
+    fn record(&mut self, entry: &str) -> io::Result<()> {
+        self.entries.push(entry.to_string());
+        let Some(path) = &self.history else {
+            return Ok(());
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", entry)
+    }
+
+    // Las entradas de varias líneas se reagrupan igual que al escribirlas
+    fn load_history(&self) -> Vec<String> {
+        let Some(text) = self
+            .history
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        let mut buffer = String::new();
+        for line in text.lines() {
+            if buffer.is_empty() && line.starts_with(':') {
+                entries.push(line.to_string());
+                continue;
+            }
+            buffer.push_str(line);
+            buffer.push('\n');
+            if !self.is_incomplete(&buffer) {
+                entries.push(std::mem::take(&mut buffer).trim_end().to_string());
+            }
+        }
+        if !buffer.is_empty() {
+            entries.push(buffer.trim_end().to_string());
+        }
+        entries
+    }
+
+    // Falta cerrar algún `{` o `(`
+    fn is_incomplete(&self, source: &str) -> bool {
+        let (tokens, _) = Lexer::new(source, &self.config).tokenize();
+        let depth: i64 = tokens
+            .iter()
+            .map(|token| match token.kind {
+                Token::LeftParen | Token::LeftBrace => 1,
+                Token::RightParen | Token::RightBrace => -1,
+                _ => 0,
+            })
+            .sum();
+        depth > 0
+    }
+
+    fn report(&self, diagnostic: Diagnostic, source: &str) {
+        eprint!(
+            "{}",
+            diagnostic.render(
+                SOURCE_NAME,
+                source,
+                &self.messages,
+                &self.config,
+                self.color
+            )
+        );
+    }
+
+    fn tokenize(&self, source: &str) -> Option<Vec<TokenData>> {
+        let (tokens, errors) = Lexer::new(source, &self.config).tokenize();
+        for e in &errors {
+            self.report(Diagnostic::from(e), source);
+        }
+        errors.is_empty().then_some(tokens)
+    }
+
+    // Se admite omitir el `;` final, como en `x = 3`
+    fn parse(&self, source: &str) -> Option<Vec<Stmt>> {
+        let (ast, errors) = Parser::new(self.tokenize(source)?).parse();
+        if errors.is_empty() {
+            return Some(ast);
+        }
+        let terminated = format!("{};", source.trim_end());
+        let (tokens, _) = Lexer::new(&terminated, &self.config).tokenize();
+        if let (ast, retry_errors) = Parser::new(tokens).parse()
+            && retry_errors.is_empty()
+        {
+            return Some(ast);
+        }
+        for e in &errors {
+            self.report(Diagnostic::from(e), source);
+        }
+        None
+    }
+
+    fn eval(&mut self, source: &str) {
+        // Una expresión suelta, aunque no pueda ser sentencia (`1 + 2`), se evalúa y se muestra
+        let (tokens, _) = Lexer::new(source, &self.config).tokenize();
+        let (checker, result) = match Parser::new(tokens).parse_standalone_expression() {
+            Ok(expr) => {
+                let Some(checker) =
+                    self.type_check(source, |checker| checker.check_expression(&expr))
+                else {
+                    return;
+                };
+                let result = self.interpreter.evaluate_expression(&expr).map(|value| {
+                    if value != Value::Void {
+                        println!("{}", value);
+                    }
+                });
+                (checker, result)
+            }
+            Err(_) => match self.parse(source) {
+                Some(ast) => {
+                    let Some(checker) = self.type_check(source, |checker| checker.check(&ast))
+                    else {
+                        return;
+                    };
+                    (checker, self.interpreter.interpret(ast))
+                }
+                None => return,
+            },
+        };
+        // Las declaraciones quedan para las entradas siguientes solo si la ejecución terminó
+        match result {
+            Ok(()) => self.checker = checker,
+            Err(e) => self.report(Diagnostic::from(&e), source),
+        }
+    }
+
+    // Una entrada con errores de tipo no se ejecuta; el verificador devuelto
+    // ya conoce sus declaraciones
+    fn type_check(
+        &self,
+        source: &str,
+        check: impl FnOnce(&mut TypeChecker) -> Result<(), Vec<TypeError>>,
+    ) -> Option<TypeChecker> {
+        let mut checker = self.checker.clone();
+        match check(&mut checker) {
+            Ok(()) => Some(checker),
+            Err(errors) => {
+                for e in &errors {
+                    self.report(Diagnostic::from(e), source);
+                }
+                None
+            }
+        }
+    }
+
+    // Devuelve `false` si hay que salir
+    fn command(&mut self, line: &str) -> bool {
+        let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match name {
+            ":ast" => {
+                if let Some(ast) = self.parse(rest) {
+                    println!("{:#?}", ast);
+                }
+            }
+            ":tokens" => {
+                if let Some(tokens) = self.tokenize(rest) {
+                    for token in tokens {
+                        println!("{}:{}\t{:?}", token.line, token.col, token.kind);
+                    }
+                }
+            }
+            ":env" => self.print_environment(),
+            ":reset" => {
+                self.interpreter = Self::fresh_interpreter(&self.config, self.max_call_depth);
+                self.checker = TypeChecker::new();
+                println!("Entorno reiniciado");
+            }
+            ":historial" => {
+                for (i, entry) in self.entries.iter().enumerate() {
+                    println!("{:>4}  {}", i + 1, entry.replace('\n', "\n      "));
+                }
+            }
+            ":repetir" => {
+                let entry = rest
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| self.entries.get(n.wrapping_sub(1)))
+                    .cloned();
+                match entry {
+                    Some(entry) if entry.starts_with(':') => return self.command(&entry),
+                    Some(entry) => self.eval(&entry),
+                    None => eprintln!("No existe la entrada {} del historial", rest.trim()),
+                }
+            }
+            ":salir" | ":quit" => return false,
+            ":ayuda" | ":help" => {
+                println!(":ast <código>     Muestra el AST sin ejecutar");
+                println!(":tokens <código>  Muestra los tokens");
+                println!(":env              Muestra las variables y funciones definidas");
+                println!(":reset            Descarta todas las definiciones");
+                println!(
+                    ":historial        Lista las entradas anteriores, también de otras sesiones"
+                );
+                println!(":repetir <n>      Vuelve a ejecutar la entrada n del historial");
+                println!(":salir            Termina la sesión");
+            }
+            _ => eprintln!("Comando desconocido: {} (pruebe :ayuda)", name),
+        }
+        true
+    }
+
+    fn print_environment(&self) {
+        for (name, var) in self.interpreter.globals() {
+            println!("{} {} = {}", self.type_name(&var.var_type), name, var.value);
+        }
+        for function in self.interpreter.functions() {
+            let params: Vec<String> = function
+                .params
+                .iter()
+                .map(|(ty, name)| {
+                    format!("{} {}", self.type_name(ty), name)
+                        .trim_end()
+                        .to_string()
+                })
+                .collect();
+            println!(
+                "{} {}({}){}",
+                self.type_name(&function.return_type),
+                function.name,
+                params.join(", "),
+                if function.native { " [nativa]" } else { "" }
+            );
+        }
+    }
+
+    // Los tipos se muestran con las palabras de la sintaxis configurada
+    fn type_name(&self, ty: &Token) -> String {
+        let types = &self.config.types;
+        match ty {
+            Token::IntType => types.int_type.clone(),
+            Token::BigIntType => types.big_int_type.clone(),
+            Token::FloatType => types.float_type.clone(),
+            Token::DoubleType => types.double_type.clone(),
+            Token::StringType => types.string_type.clone(),
+            Token::BoolType => types.bool_type.clone(),
+            Token::CharType => types.char_type.clone(),
+            Token::VoidType => types.void_type.clone(),
+            other => format!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// Junto a cada programa pueden vivir su entrada (.in), la salida estándar (.out),
+// la salida de errores (.err) y el código de salida (.exit); un archivo ausente
+// equivale a vacío o a código 0
+const INPUT: &str = "in";
+const STDOUT: &str = "out";
+const STDERR: &str = "err";
+const EXIT_CODE: &str = "exit";
+
+pub struct Mismatch {
+    pub stream: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+pub enum TestResult {
+    Passed,
+    Blessed,
+    Failed(Vec<Mismatch>),
+}
+
+// Recorre los directorios recibidos (sin descender) y devuelve los programas ordenados
+pub fn discover(paths: &[PathBuf], extension: &str) -> io::Result<Vec<PathBuf>> {
+    let mut programs = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            for entry in fs::read_dir(path)? {
+                let file = entry?.path();
+                if file.extension().is_some_and(|ext| ext == extension) {
+                    programs.push(file);
+                }
+            }
+        } else {
+            programs.push(path.clone());
+        }
+    }
+    programs.sort();
+    Ok(programs)
+}
+
+// Ejecuta el programa con el propio intérprete como proceso hijo, así la
+// salida de errores y el código de salida son los mismos que vería un usuario
+pub fn run_test(
+    executable: &Path,
+    program: &Path,
+    flags: &[String],
+    bless: bool,
+) -> io::Result<TestResult> {
+    let stdin = match fs::File::open(program.with_extension(INPUT)) {
+        Ok(file) => Stdio::from(file),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Stdio::null(),
+        Err(e) => return Err(e),
+    };
+    let output = Command::new(executable)
+        .args(flags)
+        .arg(program)
+        .stdin(stdin)
+        .env("NO_COLOR", "1")
+        .output()?;
+
+    let actual = [
+        (STDOUT, String::from_utf8_lossy(&output.stdout).into_owned()),
+        (STDERR, String::from_utf8_lossy(&output.stderr).into_owned()),
+        (
+            EXIT_CODE,
+            output
+                .status
+                .code()
+                .map_or_else(|| "señal".to_string(), |code| code.to_string()),
+        ),
+    ];
+
+    if bless {
+        for (stream, text) in &actual {
+            bless_expectation(&program.with_extension(stream), stream, text)?;
+        }
+        return Ok(TestResult::Blessed);
+    }
+
+    let mut mismatches = Vec::new();
+    for (stream, text) in actual {
+        let expected = read_expectation(&program.with_extension(stream), stream)?;
+        if expected != text {
+            mismatches.push(Mismatch {
+                stream,
+                expected,
+                actual: text,
+            });
+        }
+    }
+    if mismatches.is_empty() {
+        Ok(TestResult::Passed)
+    } else {
+        Ok(TestResult::Failed(mismatches))
+    }
+}
+
+fn read_expectation(path: &Path, stream: &str) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(text) if stream == EXIT_CODE => Ok(text.trim().to_string()),
+        Ok(text) => Ok(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(default_expectation(stream)),
+        Err(e) => Err(e),
+    }
+}
+
+// La salida estándar siempre se guarda; el resto solo cuando difiere del valor por defecto
+fn bless_expectation(path: &Path, stream: &str, text: &str) -> io::Result<()> {
+    if stream != STDOUT && text == default_expectation(stream) {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    if stream == EXIT_CODE {
+        fs::write(path, format!("{}\n", text))
+    } else {
+        fs::write(path, text)
+    }
+}
+
+fn default_expectation(stream: &str) -> String {
+    match stream {
+        EXIT_CODE => "0".to_string(),
+        _ => String::new(),
+    }
+}
@@ -1,4 +1,45 @@
-use crate::core::token::Token;
+use crate::core::token::{Token, TokenData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+// `end` apunta justo después del último carácter del nodo
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+impl From<&TokenData> for Span {
+    fn from(token: &TokenData) -> Self {
+        Span::new(
+            Position {
+                line: token.line,
+                column: token.col,
+                offset: token.start,
+            },
+            Position {
+                line: token.end_line,
+                column: token.end_col,
+                offset: token.end,
+            },
+        )
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Expr {
@@ -6,18 +47,50 @@ pub enum Expr {
         left: Box<Expr>,
         operator: Token,
         right: Box<Expr>,
+        span: Span,
+    },
+    // `&&` y `||` evalúan el operando derecho solo si hace falta
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+        span: Span,
     },
     Unary {
         operator: Token,
         right: Box<Expr>,
+        span: Span,
+    },
+    Literal {
+        value: Token,
+        span: Span,
+    },
+    Variable {
+        name: String,
+        span: Span,
     },
-    Literal(Token),
-    Variable(String),
     Call {
         callee: String,
         arguments: Vec<Expr>,
+        span: Span,
+    },
+    Input {
+        span: Span,
     },
-    Input,
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Binary { span, .. }
+            | Expr::Logical { span, .. }
+            | Expr::Unary { span, .. }
+            | Expr::Literal { span, .. }
+            | Expr::Variable { span, .. }
+            | Expr::Call { span, .. }
+            | Expr::Input { span } => *span,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -26,38 +99,89 @@ pub enum Stmt {
         ty: Token,
         name: String,
         initializer: Option<Expr>,
+        span: Span,
+    },
+    Expression {
+        expr: Expr,
+        span: Span,
+    },
+    Return {
+        value: Option<Expr>,
+        span: Span,
     },
-    Expression(Expr),
-    Return(Option<Expr>),
     Assignment {
         name: String,
         value: Expr,
+        span: Span,
     },
     If {
         condition: Expr,
         then_branch: Vec<Stmt>,
         else_branch: Option<Vec<Stmt>>,
+        span: Span,
     },
     While {
         condition: Expr,
         body: Vec<Stmt>,
+        span: Span,
+    },
+    For {
+        initializer: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        increment: Option<Box<Stmt>>,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+    Block {
+        statements: Vec<Stmt>,
+        span: Span,
     },
-    Block(Vec<Stmt>),
     Function {
         return_type: Token,
         name: String,
         params: Vec<(Token, String)>,
         body: Vec<Stmt>,
+        span: Span,
     },
     Print {
         value: Expr,
+        span: Span,
     },
     Println {
         value: Expr,
+        span: Span,
+    },
+    Break {
+        span: Span,
+    },
+    Continue {
+        span: Span,
     },
     Switch {
         condition: Expr,
         cases: Vec<(Expr, Vec<Stmt>)>,
         default: Option<Vec<Stmt>>,
+        span: Span,
     },
 }
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::VarDeclaration { span, .. }
+            | Stmt::Expression { span, .. }
+            | Stmt::Return { span, .. }
+            | Stmt::Assignment { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::For { span, .. }
+            | Stmt::Block { span, .. }
+            | Stmt::Function { span, .. }
+            | Stmt::Print { span, .. }
+            | Stmt::Println { span, .. }
+            | Stmt::Break { span }
+            | Stmt::Continue { span }
+            | Stmt::Switch { span, .. } => *span,
+        }
+    }
+}
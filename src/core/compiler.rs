@@ -0,0 +1,1024 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::core::ast::{Expr, Span, Stmt};
+use crate::core::interpreter::{Interpreter, Value};
+use crate::core::messages::{Message, MessageId};
+use crate::core::token::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Constant(u16),
+    Pop,
+    PopN(u16),
+
+    // Variables
+    GetLocal(u16),
+    SetLocal(u16),
+    NewCell(u16),
+    MakeCell(u16),
+    GetCell(u16),
+    SetCell(u16),
+    GetUpvalue(u16),
+    SetUpvalue(u16),
+    DefineGlobal(u16, u16),
+    GetGlobal(u16),
+    SetGlobal(u16),
+    CheckType(u16),
+
+    // Operadores
+    Negate,
+    Not,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    GreaterEqual,
+    LessEqual,
+    CaseEqual,
+
+    // Control de flujo
+    Jump(u32),
+    JumpIfFalse(u32),
+    Call(u16, u8),
+    Closure(u16),
+    Return,
+    // Fin de una función no void sin pasar por un return
+    MissingReturn,
+    // Declaración sin valor inicial, que solo llega aquí sin verificación de tipos
+    MissingInitializer(u16),
+
+    Print,
+    Println,
+    Input,
+}
+
+// Origen de una variable capturada, relativo a la función que declara la clausura
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capture {
+    Cell(u16),
+    Upvalue(u16),
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    pub constants: Vec<Value>,
+    pub types: Vec<Token>,
+    pub spans: Vec<Span>,
+}
+
+#[derive(Debug)]
+pub struct FunctionProto {
+    pub name: u16,
+    pub param_types: Vec<Token>,
+    pub return_type: Token,
+    pub captures: Vec<Capture>,
+    pub chunk: Chunk,
+}
+
+#[derive(Debug)]
+pub struct Program {
+    pub main: Rc<FunctionProto>,
+    pub functions: Vec<Rc<FunctionProto>>,
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    pub message: Message,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+impl CompileError {
+    pub fn new(message: impl Into<Message>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            line: span.start.line,
+            column: span.start.column,
+            span,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.message.id {
+            MessageId::BreakWithoutTarget | MessageId::ContinueWithoutLoop => "E402",
+            MessageId::UnknownUnaryOperator
+            | MessageId::UnsupportedBinaryOperator
+            | MessageId::UnsupportedLogicalOperator => "E403",
+            _ => "E404",
+        }
+    }
+}
+
+struct Local {
+    name: String,
+    depth: usize,
+    cell: Option<u16>,
+    ty: Option<Token>,
+}
+
+struct Upvalue {
+    name: String,
+    capture: Capture,
+    ty: Option<Token>,
+}
+
+// Bucle o switch en curso: destino de `break` y, si es bucle, de `continue`
+struct Breakable {
+    is_loop: bool,
+    // Los locales declarados por encima de esta profundidad se descartan al saltar
+    depth: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+struct FunctionState {
+    chunk: Chunk,
+    locals: Vec<Local>,
+    upvalues: Vec<Upvalue>,
+    depth: usize,
+    cell_count: u16,
+    breakables: Vec<Breakable>,
+    // Nombres usados por funciones anidadas: esos locales viven en celdas compartidas
+    captured: HashSet<String>,
+    // Locales capturados que un ámbito abierto declarará más adelante: su celda se
+    // crea al entrar al ámbito para que una función anterior ya pueda verla
+    pending: Vec<Local>,
+}
+
+impl FunctionState {
+    fn new(depth: usize, body: &[Stmt]) -> Self {
+        let mut captured = HashSet::new();
+        nested_function_names(body, &mut captured);
+        Self {
+            chunk: Chunk::default(),
+            locals: Vec::new(),
+            upvalues: Vec::new(),
+            depth,
+            cell_count: 0,
+            breakables: Vec::new(),
+            captured,
+            pending: Vec::new(),
+        }
+    }
+}
+
+enum Resolved {
+    Local(u16, Option<Token>),
+    Cell(u16, Option<Token>),
+    Upvalue(u16, Option<Token>),
+    Global(u16),
+}
+
+pub struct Compiler {
+    states: Vec<FunctionState>,
+    functions: Vec<Rc<FunctionProto>>,
+    names: Vec<String>,
+    name_lookup: HashMap<String, u16>,
+    script_exits: Vec<usize>,
+    span: Span,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            states: Vec::new(),
+            functions: Vec::new(),
+            names: Vec::new(),
+            name_lookup: HashMap::new(),
+            script_exits: Vec::new(),
+            span: Span::default(),
+        }
+    }
+
+    pub fn compile(&mut self, statements: &[Stmt]) -> Result<Program, CompileError> {
+        self.states.push(FunctionState::new(0, statements));
+        for stmt in statements {
+            self.compile_stmt(stmt)?;
+            let end = self.state().chunk.code.len();
+            for exit in std::mem::take(&mut self.script_exits) {
+                self.patch(exit, end);
+            }
+        }
+
+        let state = self.states.pop().unwrap();
+        let name = self.intern("", Span::default())?;
+        Ok(Program {
+            main: Rc::new(FunctionProto {
+                name,
+                param_types: Vec::new(),
+                return_type: Token::VoidType,
+                captures: Vec::new(),
+                chunk: state.chunk,
+            }),
+            functions: std::mem::take(&mut self.functions),
+            names: std::mem::take(&mut self.names),
+        })
+    }
+
+    fn compile_stmt(&mut self, stmt: &Stmt) -> Result<(), CompileError> {
+        let previous = std::mem::replace(&mut self.span, stmt.span());
+        match stmt {
+            Stmt::VarDeclaration {
+                ty,
+                name,
+                initializer,
+                span,
+            } => {
+                match initializer {
+                    Some(initializer) => self.compile_expr(initializer)?,
+                    None => {
+                        let name = self.intern(name, *span)?;
+                        self.emit(Instruction::MissingInitializer(name));
+                    }
+                }
+                let ty_index = self.type_index(ty, *span)?;
+
+                if self.states.len() == 1 && self.state().depth == 0 {
+                    let name = self.intern(name, *span)?;
+                    self.emit(Instruction::DefineGlobal(name, ty_index));
+                } else {
+                    self.emit(Instruction::CheckType(ty_index));
+                    if let Some(cell) = self.declare_local(name, Some(ty.clone()), *span)? {
+                        self.emit(Instruction::MakeCell(cell));
+                    }
+                }
+            }
+            Stmt::Assignment { name, value, span } => {
+                self.compile_expr(value)?;
+                match self.resolve(name, *span)? {
+                    Resolved::Local(slot, ty) => {
+                        self.emit_check(ty, *span)?;
+                        self.emit(Instruction::SetLocal(slot));
+                    }
+                    Resolved::Cell(cell, ty) => {
+                        self.emit_check(ty, *span)?;
+                        self.emit(Instruction::SetCell(cell));
+                    }
+                    Resolved::Upvalue(index, ty) => {
+                        self.emit_check(ty, *span)?;
+                        self.emit(Instruction::SetUpvalue(index));
+                    }
+                    Resolved::Global(name) => self.emit(Instruction::SetGlobal(name)),
+                }
+            }
+            Stmt::Expression { expr, .. } => {
+                self.compile_expr(expr)?;
+                self.emit(Instruction::Pop);
+            }
+            Stmt::Print { value, .. } => {
+                self.compile_expr(value)?;
+                self.emit(Instruction::Print);
+            }
+            Stmt::Println { value, .. } => {
+                self.compile_expr(value)?;
+                self.emit(Instruction::Println);
+            }
+            Stmt::Return { value, span } => {
+                match value {
+                    Some(expr) => self.compile_expr(expr)?,
+                    None => self.emit_constant(Value::Void, *span)?,
+                }
+                if self.states.len() > 1 {
+                    self.emit(Instruction::Return);
+                } else {
+                    // Un return de nivel superior solo termina la sentencia actual
+                    self.emit(Instruction::Pop);
+                    let count = self.state().locals.len();
+                    if count > 0 {
+                        self.emit(Instruction::PopN(count as u16));
+                    }
+                    let exit = self.emit_jump(Instruction::Jump(0));
+                    self.script_exits.push(exit);
+                }
+            }
+            Stmt::Break { span } => {
+                let Some(index) = self.state().breakables.len().checked_sub(1) else {
+                    return Err(CompileError::new(
+                        Message::new(
+                            MessageId::BreakWithoutTarget,
+                            vec![Token::Break, Token::Switch],
+                        ),
+                        *span,
+                    ));
+                };
+                let jump = self.emit_exit_jump(index);
+                self.state_mut().breakables[index].breaks.push(jump);
+            }
+            Stmt::Continue { span } => {
+                let Some(index) = self.state().breakables.iter().rposition(|b| b.is_loop) else {
+                    return Err(CompileError::new(
+                        Message::new(MessageId::ContinueWithoutLoop, vec![Token::Continue]),
+                        *span,
+                    ));
+                };
+                let jump = self.emit_exit_jump(index);
+                self.state_mut().breakables[index].continues.push(jump);
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.compile_expr(condition)?;
+                let else_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                self.compile_scoped(then_branch)?;
+                let end_jump = self.emit_jump(Instruction::Jump(0));
+                self.patch_here(else_jump);
+                if let Some(branch) = else_branch {
+                    self.compile_scoped(branch)?;
+                }
+                self.patch_here(end_jump);
+            }
+            Stmt::While {
+                condition, body, ..
+            } => {
+                let start = self.state().chunk.code.len();
+                self.compile_expr(condition)?;
+                let exit_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                self.begin_breakable(true);
+                self.compile_scoped(body)?;
+                let breakable = self.end_breakable();
+                for jump in breakable.continues {
+                    self.patch(jump, start);
+                }
+                self.emit(Instruction::Jump(start as u32));
+                self.patch_here(exit_jump);
+                for jump in breakable.breaks {
+                    self.patch_here(jump);
+                }
+            }
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                self.begin_scope();
+                if let Some(init) = initializer {
+                    self.prepare_cells(std::slice::from_ref(init));
+                    self.compile_stmt(init)?;
+                }
+                let start = self.state().chunk.code.len();
+                let mut exit_jump = None;
+                if let Some(condition) = condition {
+                    self.compile_expr(condition)?;
+                    exit_jump = Some(self.emit_jump(Instruction::JumpIfFalse(0)));
+                }
+                self.begin_breakable(true);
+                self.compile_scoped(body)?;
+                let breakable = self.end_breakable();
+                for jump in breakable.continues {
+                    self.patch_here(jump);
+                }
+                if let Some(increment) = increment {
+                    self.compile_stmt(increment)?;
+                }
+                self.emit(Instruction::Jump(start as u32));
+                if let Some(exit_jump) = exit_jump {
+                    self.patch_here(exit_jump);
+                }
+                for jump in breakable.breaks {
+                    self.patch_here(jump);
+                }
+                self.end_scope();
+            }
+            Stmt::Block { statements, .. } => self.compile_scoped(statements)?,
+            Stmt::Function {
+                return_type,
+                name,
+                params,
+                body,
+                span,
+            } => self.compile_function(return_type, name, params, body, *span)?,
+            Stmt::Switch {
+                condition,
+                cases,
+                default,
+                span,
+            } => {
+                self.begin_scope();
+                self.compile_expr(condition)?;
+                self.declare_local("", None, *span)?;
+                let slot = (self.state().locals.len() - 1) as u16;
+                self.begin_breakable(false);
+
+                let mut end_jumps = Vec::new();
+                for (case_expr, body) in cases {
+                    self.emit(Instruction::GetLocal(slot));
+                    self.compile_expr(case_expr)?;
+                    self.emit(Instruction::CaseEqual);
+                    let next_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                    self.compile_scoped(body)?;
+                    end_jumps.push(self.emit_jump(Instruction::Jump(0)));
+                    self.patch_here(next_jump);
+                }
+                if let Some(body) = default {
+                    self.compile_scoped(body)?;
+                }
+                let breakable = self.end_breakable();
+                for jump in end_jumps.into_iter().chain(breakable.breaks) {
+                    self.patch_here(jump);
+                }
+                self.end_scope();
+            }
+        }
+        self.span = previous;
+        Ok(())
+    }
+
+    fn compile_function(
+        &mut self,
+        return_type: &Token,
+        name: &str,
+        params: &[(Token, String)],
+        body: &[Stmt],
+        span: Span,
+    ) -> Result<(), CompileError> {
+        let name = self.intern(name, span)?;
+        self.states.push(FunctionState::new(1, body));
+
+        for (ty, param) in params {
+            self.declare_local(param, Some(ty.clone()), span)?;
+        }
+        // Los parámetros capturados se copian a una celda al entrar
+        let cells: Vec<(u16, u16)> = self
+            .state()
+            .locals
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, local)| local.cell.map(|cell| (slot as u16, cell)))
+            .collect();
+        for (slot, cell) in cells {
+            self.emit(Instruction::GetLocal(slot));
+            self.emit(Instruction::MakeCell(cell));
+            self.emit(Instruction::Pop);
+        }
+
+        self.prepare_cells(body);
+        for stmt in body {
+            self.compile_stmt(stmt)?;
+        }
+        if *return_type == Token::VoidType {
+            self.emit_constant(Value::Void, span)?;
+            self.emit(Instruction::Return);
+        } else {
+            self.emit(Instruction::MissingReturn);
+        }
+
+        let state = self.states.pop().unwrap();
+        let proto = FunctionProto {
+            name,
+            param_types: params.iter().map(|(ty, _)| ty.clone()).collect(),
+            return_type: return_type.clone(),
+            captures: state.upvalues.iter().map(|u| u.capture).collect(),
+            chunk: state.chunk,
+        };
+        let index = self.functions.len();
+        if index > u16::MAX as usize {
+            return Err(CompileError::new(MessageId::TooManyFunctions, span));
+        }
+        self.functions.push(Rc::new(proto));
+        self.emit(Instruction::Closure(index as u16));
+        Ok(())
+    }
+
+    fn compile_expr(&mut self, expr: &Expr) -> Result<(), CompileError> {
+        let previous = std::mem::replace(&mut self.span, expr.span());
+        match expr {
+            Expr::Literal { value, span } => {
+                self.emit_constant(Interpreter::literal_value(value.clone()), *span)?
+            }
+            Expr::Variable { name, span } => {
+                let instruction = match self.resolve(name, *span)? {
+                    Resolved::Local(slot, _) => Instruction::GetLocal(slot),
+                    Resolved::Cell(cell, _) => Instruction::GetCell(cell),
+                    Resolved::Upvalue(index, _) => Instruction::GetUpvalue(index),
+                    Resolved::Global(name) => Instruction::GetGlobal(name),
+                };
+                self.emit(instruction);
+            }
+            Expr::Unary {
+                operator,
+                right,
+                span,
+            } => {
+                self.compile_expr(right)?;
+                match operator {
+                    Token::Minus => self.emit(Instruction::Negate),
+                    Token::Not => self.emit(Instruction::Not),
+                    _ => {
+                        return Err(CompileError::new(
+                            Message::new(MessageId::UnknownUnaryOperator, vec![operator.clone()]),
+                            *span,
+                        ));
+                    }
+                }
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                span,
+            } => {
+                self.compile_expr(left)?;
+                self.compile_expr(right)?;
+                let instruction = match operator {
+                    Token::Plus => Instruction::Add,
+                    Token::Minus => Instruction::Subtract,
+                    Token::Multiply => Instruction::Multiply,
+                    Token::Divide => Instruction::Divide,
+                    Token::Modulo => Instruction::Modulo,
+                    Token::EqualEqual => Instruction::Equal,
+                    Token::NotEqual => Instruction::NotEqual,
+                    Token::Greater => Instruction::Greater,
+                    Token::Less => Instruction::Less,
+                    Token::GreaterEqual => Instruction::GreaterEqual,
+                    Token::LessEqual => Instruction::LessEqual,
+                    _ => {
+                        return Err(CompileError::new(
+                            Message::new(
+                                MessageId::UnsupportedBinaryOperator,
+                                vec![operator.clone()],
+                            ),
+                            *span,
+                        ));
+                    }
+                };
+                self.emit(instruction);
+            }
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                span,
+            } => {
+                self.compile_expr(left)?;
+                self.emit_bool_check(left)?;
+                match operator {
+                    Token::And => {
+                        let short_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                        self.compile_expr(right)?;
+                        self.emit_bool_check(right)?;
+                        let end_jump = self.emit_jump(Instruction::Jump(0));
+                        self.patch_here(short_jump);
+                        self.emit_constant(Value::Bool(false), *span)?;
+                        self.patch_here(end_jump);
+                    }
+                    Token::Or => {
+                        let right_jump = self.emit_jump(Instruction::JumpIfFalse(0));
+                        self.emit_constant(Value::Bool(true), *span)?;
+                        let end_jump = self.emit_jump(Instruction::Jump(0));
+                        self.patch_here(right_jump);
+                        self.compile_expr(right)?;
+                        self.emit_bool_check(right)?;
+                        self.patch_here(end_jump);
+                    }
+                    _ => {
+                        return Err(CompileError::new(
+                            Message::new(
+                                MessageId::UnsupportedLogicalOperator,
+                                vec![operator.clone()],
+                            ),
+                            *span,
+                        ));
+                    }
+                }
+            }
+            Expr::Call {
+                callee,
+                arguments,
+                span,
+            } => {
+                if arguments.len() > u8::MAX as usize {
+                    return Err(CompileError::new(MessageId::TooManyArguments, *span));
+                }
+                for arg in arguments {
+                    self.compile_expr(arg)?;
+                }
+                let name = self.intern(callee, *span)?;
+                self.emit(Instruction::Call(name, arguments.len() as u8));
+            }
+            Expr::Input { .. } => self.emit(Instruction::Input),
+        }
+        self.span = previous;
+        Ok(())
+    }
+
+    fn compile_scoped(&mut self, statements: &[Stmt]) -> Result<(), CompileError> {
+        self.begin_scope();
+        self.prepare_cells(statements);
+        for stmt in statements {
+            self.compile_stmt(stmt)?;
+        }
+        self.end_scope();
+        Ok(())
+    }
+
+    fn prepare_cells(&mut self, statements: &[Stmt]) {
+        // Las variables de nivel superior son globales y se buscan por nombre
+        if self.states.len() == 1 && self.state().depth == 0 {
+            return;
+        }
+        for stmt in statements {
+            let Stmt::VarDeclaration { ty, name, .. } = stmt else {
+                continue;
+            };
+            let state = self.state_mut();
+            if !state.captured.contains(name) {
+                continue;
+            }
+            let cell = state.cell_count;
+            state.cell_count += 1;
+            state.pending.push(Local {
+                name: name.clone(),
+                depth: state.depth,
+                cell: Some(cell),
+                ty: Some(ty.clone()),
+            });
+            self.emit(Instruction::NewCell(cell));
+        }
+    }
+
+    fn begin_breakable(&mut self, is_loop: bool) {
+        let state = self.state_mut();
+        let depth = state.depth;
+        state.breakables.push(Breakable {
+            is_loop,
+            depth,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+    }
+
+    fn end_breakable(&mut self) -> Breakable {
+        self.state_mut().breakables.pop().unwrap()
+    }
+
+    // Descarta los locales abiertos dentro del bucle o switch antes de saltar fuera
+    fn emit_exit_jump(&mut self, index: usize) -> usize {
+        let state = self.state();
+        let depth = state.breakables[index].depth;
+        let count = state
+            .locals
+            .iter()
+            .rev()
+            .take_while(|local| local.depth > depth)
+            .count();
+        if count > 0 {
+            self.emit(Instruction::PopN(count as u16));
+        }
+        self.emit_jump(Instruction::Jump(0))
+    }
+
+    fn begin_scope(&mut self) {
+        self.state_mut().depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        let state = self.state_mut();
+        state.depth -= 1;
+        let depth = state.depth;
+        state.pending.retain(|local| local.depth <= depth);
+        let mut count = 0;
+        while state
+            .locals
+            .last()
+            .is_some_and(|local| local.depth > state.depth)
+        {
+            state.locals.pop();
+            count += 1;
+        }
+        if count > 0 {
+            self.emit(Instruction::PopN(count));
+        }
+    }
+
+    fn declare_local(
+        &mut self,
+        name: &str,
+        ty: Option<Token>,
+        span: Span,
+    ) -> Result<Option<u16>, CompileError> {
+        let state = self.state_mut();
+        if state.locals.len() > u16::MAX as usize {
+            return Err(CompileError::new(MessageId::TooManyLocals, span));
+        }
+        let pending = state
+            .pending
+            .iter()
+            .position(|local| local.name == name && local.depth == state.depth);
+        let cell = if let Some(index) = pending {
+            state.pending.remove(index).cell
+        } else if state.captured.contains(name) {
+            state.cell_count += 1;
+            Some(state.cell_count - 1)
+        } else {
+            None
+        };
+        state.locals.push(Local {
+            name: name.to_string(),
+            depth: state.depth,
+            cell,
+            ty,
+        });
+        Ok(cell)
+    }
+
+    fn resolve(&mut self, name: &str, span: Span) -> Result<Resolved, CompileError> {
+        let level = self.states.len() - 1;
+        if let Some(resolved) = self.resolve_local(level, name, false) {
+            return Ok(resolved);
+        }
+        if let Some((index, ty)) = self.resolve_upvalue(level, name) {
+            return Ok(Resolved::Upvalue(index, ty));
+        }
+        Ok(Resolved::Global(self.intern(name, span)?))
+    }
+
+    // Con `pending` también se encuentran los locales que su ámbito declarará
+    // después, como los vería una función anidada al ser llamada
+    fn resolve_local(&self, level: usize, name: &str, pending: bool) -> Option<Resolved> {
+        let state = &self.states[level];
+        let declared = state
+            .locals
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, local)| local.name == name);
+        let later = state
+            .pending
+            .iter()
+            .rev()
+            .find(|local| pending && local.name == name);
+        match (declared, later) {
+            (Some((_, local)), Some(later)) if later.depth > local.depth => {
+                Some(Resolved::Cell(later.cell?, later.ty.clone()))
+            }
+            (Some((slot, local)), _) => Some(match local.cell {
+                Some(cell) => Resolved::Cell(cell, local.ty.clone()),
+                None => Resolved::Local(slot as u16, local.ty.clone()),
+            }),
+            (None, Some(later)) => Some(Resolved::Cell(later.cell?, later.ty.clone())),
+            (None, None) => None,
+        }
+    }
+
+    fn resolve_upvalue(&mut self, level: usize, name: &str) -> Option<(u16, Option<Token>)> {
+        if level == 0 {
+            return None;
+        }
+        if let Some(index) = self.states[level]
+            .upvalues
+            .iter()
+            .position(|u| u.name == name)
+        {
+            let ty = self.states[level].upvalues[index].ty.clone();
+            return Some((index as u16, ty));
+        }
+
+        let (capture, ty) = match self.resolve_local(level - 1, name, true) {
+            Some(Resolved::Cell(cell, ty)) => (Capture::Cell(cell), ty),
+            Some(_) => return None,
+            None => {
+                let (index, ty) = self.resolve_upvalue(level - 1, name)?;
+                (Capture::Upvalue(index), ty)
+            }
+        };
+        let upvalues = &mut self.states[level].upvalues;
+        upvalues.push(Upvalue {
+            name: name.to_string(),
+            capture,
+            ty: ty.clone(),
+        });
+        Some(((upvalues.len() - 1) as u16, ty))
+    }
+
+    fn intern(&mut self, name: &str, span: Span) -> Result<u16, CompileError> {
+        if let Some(index) = self.name_lookup.get(name) {
+            return Ok(*index);
+        }
+        if self.names.len() > u16::MAX as usize {
+            return Err(CompileError::new(MessageId::TooManyIdentifiers, span));
+        }
+        let index = self.names.len() as u16;
+        self.names.push(name.to_string());
+        self.name_lookup.insert(name.to_string(), index);
+        Ok(index)
+    }
+
+    fn type_index(&mut self, ty: &Token, span: Span) -> Result<u16, CompileError> {
+        let types = &mut self.state_mut().chunk.types;
+        if let Some(index) = types.iter().position(|t| t == ty) {
+            return Ok(index as u16);
+        }
+        if types.len() > u16::MAX as usize {
+            return Err(CompileError::new(MessageId::TooManyTypes, span));
+        }
+        types.push(ty.clone());
+        Ok((types.len() - 1) as u16)
+    }
+
+    fn emit_check(&mut self, ty: Option<Token>, span: Span) -> Result<(), CompileError> {
+        if let Some(ty) = ty {
+            let index = self.type_index(&ty, span)?;
+            self.emit(Instruction::CheckType(index));
+        }
+        Ok(())
+    }
+
+    // El error de tipo apunta al operando, no a la expresión lógica completa
+    fn emit_bool_check(&mut self, operand: &Expr) -> Result<(), CompileError> {
+        let previous = std::mem::replace(&mut self.span, operand.span());
+        self.emit_check(Some(Token::BoolType), operand.span())?;
+        self.span = previous;
+        Ok(())
+    }
+
+    fn emit_constant(&mut self, value: Value, span: Span) -> Result<(), CompileError> {
+        let constants = &mut self.state_mut().chunk.constants;
+        if constants.len() > u16::MAX as usize {
+            return Err(CompileError::new(MessageId::TooManyConstants, span));
+        }
+        constants.push(value);
+        let index = (constants.len() - 1) as u16;
+        self.emit(Instruction::Constant(index));
+        Ok(())
+    }
+
+    fn emit(&mut self, instruction: Instruction) {
+        let span = self.span;
+        let chunk = &mut self.state_mut().chunk;
+        chunk.code.push(instruction);
+        chunk.spans.push(span);
+    }
+
+    fn emit_jump(&mut self, instruction: Instruction) -> usize {
+        self.emit(instruction);
+        self.state().chunk.code.len() - 1
+    }
+
+    fn patch_here(&mut self, at: usize) {
+        let target = self.state().chunk.code.len();
+        self.patch(at, target);
+    }
+
+    fn patch(&mut self, at: usize, target: usize) {
+        let code = &mut self.state_mut().chunk.code;
+        code[at] = match code[at] {
+            Instruction::Jump(_) => Instruction::Jump(target as u32),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target as u32),
+            other => other,
+        };
+    }
+
+    fn state(&self) -> &FunctionState {
+        self.states.last().unwrap()
+    }
+
+    fn state_mut(&mut self) -> &mut FunctionState {
+        self.states.last_mut().unwrap()
+    }
+}
+
+// Todos los nombres referenciados dentro de funciones declaradas en `statements`
+fn nested_function_names(statements: &[Stmt], names: &mut HashSet<String>) {
+    for stmt in statements {
+        match stmt {
+            Stmt::Function { body, .. } => referenced_names(body, names),
+            Stmt::Block { statements, .. } => nested_function_names(statements, names),
+            Stmt::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                nested_function_names(then_branch, names);
+                if let Some(branch) = else_branch {
+                    nested_function_names(branch, names);
+                }
+            }
+            Stmt::While { body, .. } | Stmt::For { body, .. } => nested_function_names(body, names),
+            Stmt::Switch { cases, default, .. } => {
+                for (_, body) in cases {
+                    nested_function_names(body, names);
+                }
+                if let Some(body) = default {
+                    nested_function_names(body, names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn referenced_names(statements: &[Stmt], names: &mut HashSet<String>) {
+    for stmt in statements {
+        match stmt {
+            Stmt::VarDeclaration { initializer, .. } => {
+                if let Some(expr) = initializer {
+                    expr_names(expr, names);
+                }
+            }
+            Stmt::Assignment { name, value, .. } => {
+                names.insert(name.clone());
+                expr_names(value, names);
+            }
+            Stmt::Expression { expr, .. } => expr_names(expr, names),
+            Stmt::Print { value, .. } | Stmt::Println { value, .. } => expr_names(value, names),
+            Stmt::Return { value, .. } => {
+                if let Some(expr) = value {
+                    expr_names(expr, names);
+                }
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                expr_names(condition, names);
+                referenced_names(then_branch, names);
+                if let Some(branch) = else_branch {
+                    referenced_names(branch, names);
+                }
+            }
+            Stmt::While {
+                condition, body, ..
+            } => {
+                expr_names(condition, names);
+                referenced_names(body, names);
+            }
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                if let Some(init) = initializer {
+                    referenced_names(std::slice::from_ref(init.as_ref()), names);
+                }
+                if let Some(condition) = condition {
+                    expr_names(condition, names);
+                }
+                if let Some(increment) = increment {
+                    referenced_names(std::slice::from_ref(increment.as_ref()), names);
+                }
+                referenced_names(body, names);
+            }
+            Stmt::Block { statements, .. } => referenced_names(statements, names),
+            Stmt::Break { .. } | Stmt::Continue { .. } => {}
+            Stmt::Function { body, .. } => referenced_names(body, names),
+            Stmt::Switch {
+                condition,
+                cases,
+                default,
+                ..
+            } => {
+                expr_names(condition, names);
+                for (case_expr, body) in cases {
+                    expr_names(case_expr, names);
+                    referenced_names(body, names);
+                }
+                if let Some(body) = default {
+                    referenced_names(body, names);
+                }
+            }
+        }
+    }
+}
+
+fn expr_names(expr: &Expr, names: &mut HashSet<String>) {
+    match expr {
+        Expr::Variable { name, .. } => {
+            names.insert(name.clone());
+        }
+        Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+            expr_names(left, names);
+            expr_names(right, names);
+        }
+        Expr::Unary { right, .. } => expr_names(right, names),
+        Expr::Call { arguments, .. } => {
+            for arg in arguments {
+                expr_names(arg, names);
+            }
+        }
+        Expr::Literal { .. } | Expr::Input { .. } => {}
+    }
+}
@@ -0,0 +1,242 @@
+use std::io::IsTerminal;
+
+use crate::core::ast::Span;
+use crate::core::compiler::CompileError;
+use crate::core::interpreter::RuntimeError;
+use crate::core::lexer::LexicalError;
+use crate::core::messages::{Message, MessageCatalog, MessageId};
+use crate::core::parser::ParseError;
+use crate::core::translator::TranslateError;
+use crate::core::type_checker::TypeError;
+use crate::persistence::models::SyntaxConfig;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// Llamadas de la traza que se muestran en un error de ejecución
+const SHOWN_FRAMES: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub message: Message,
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub notes: Vec<Message>,
+    pub help: Option<Message>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        code: &'static str,
+        message: impl Into<Message>,
+        line: usize,
+        column: usize,
+    ) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            line,
+            column,
+            end_column: column + 1,
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn with_end_column(mut self, end_column: usize) -> Self {
+        self.end_column = end_column.max(self.column + 1);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<Message>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<Message>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    fn from_span(code: &'static str, message: Message, span: Span) -> Self {
+        let diagnostic = Self::new(code, message, span.start.line, span.start.column);
+        if span.end.line == span.start.line {
+            diagnostic.with_end_column(span.end.column)
+        } else {
+            diagnostic
+        }
+    }
+
+    // Los textos salen del catálogo; uno vacío deja los mensajes originales.
+    // Los tipos y palabras reservadas se muestran como los escribe `syntax`
+    pub fn render(
+        &self,
+        file_name: &str,
+        source: &str,
+        messages: &MessageCatalog,
+        syntax: &SyntaxConfig,
+        color: bool,
+    ) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{}{}{}", style, text, RESET)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let mut out = format!(
+            "{}{}\n",
+            paint(RED, &format!("error[{}]", self.code)),
+            paint(
+                BOLD,
+                &format!(": {}", messages.translate(&self.message, syntax))
+            )
+        );
+        // Los errores sin posición (línea 0) no vienen de ninguna línea del programa
+        if self.line == 0 {
+            out.push_str(&format!("{}{} {}\n", gutter, paint(BLUE, "-->"), file_name));
+        } else {
+            out.push_str(&format!(
+                "{}{} {}:{}:{}\n",
+                gutter,
+                paint(BLUE, "-->"),
+                file_name,
+                self.line,
+                self.column
+            ));
+        }
+
+        if let Some(text) = self
+            .line
+            .checked_sub(1)
+            .and_then(|index| source.lines().nth(index))
+        {
+            // Se conservan los tabuladores para que el subrayado quede alineado
+            let padding: String = text
+                .chars()
+                .take(self.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let width = self.end_column.saturating_sub(self.column).max(1);
+
+            out.push_str(&format!("{} {}\n", gutter, paint(BLUE, "|")));
+            out.push_str(&format!(
+                "{} {} {}\n",
+                paint(BLUE, &line_number),
+                paint(BLUE, "|"),
+                text
+            ));
+            out.push_str(&format!(
+                "{} {} {}{}\n",
+                gutter,
+                paint(BLUE, "|"),
+                padding,
+                paint(RED, &"^".repeat(width))
+            ));
+        }
+
+        for note in &self.notes {
+            let note = messages.translate(note, syntax);
+            out.push_str(&format!("{} {} note: {}\n", gutter, paint(BLUE, "="), note));
+        }
+        if let Some(help) = &self.help {
+            let help = messages.translate(help, syntax);
+            out.push_str(&format!("{} {} help: {}\n", gutter, paint(BLUE, "="), help));
+        }
+        out
+    }
+}
+
+// Colores solo en terminales interactivas y respetando NO_COLOR
+pub fn use_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal()
+}
+
+impl From<&LexicalError> for Diagnostic {
+    fn from(e: &LexicalError) -> Self {
+        Diagnostic::new(e.code(), e.message.clone(), e.line, e.column).with_end_column(e.end_column)
+    }
+}
+
+// Sugerencias para los errores más frecuentes
+fn help_for(message: &Message) -> Option<Message> {
+    match message.id {
+        MessageId::ExpectedSemiAfterDeclaration
+        | MessageId::ExpectedSemiAfterExpression
+        | MessageId::ExpectedSemiAfterAssignment
+        | MessageId::ExpectedSemiAfterReturn
+        | MessageId::ExpectedSemiAfterKeyword => Some(MessageId::HelpMissingSemicolon.into()),
+        MessageId::UndefinedVariable | MessageId::AssignToUndefined => Some(Message::new(
+            MessageId::HelpDeclareVariable,
+            message.args.clone(),
+        )),
+        _ => None,
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(e: &ParseError) -> Self {
+        Diagnostic {
+            help: help_for(&e.message),
+            ..Diagnostic::new(e.code(), e.message.clone(), e.line, e.column)
+                .with_end_column(e.end_column)
+        }
+    }
+}
+
+impl From<&TypeError> for Diagnostic {
+    fn from(e: &TypeError) -> Self {
+        Diagnostic {
+            help: help_for(&e.message),
+            ..Diagnostic::from_span(e.code(), e.message.clone(), e.span)
+        }
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(e: &CompileError) -> Self {
+        Diagnostic::from_span(e.code(), e.message.clone(), e.span)
+    }
+}
+
+impl From<&TranslateError> for Diagnostic {
+    fn from(e: &TranslateError) -> Self {
+        Diagnostic::new(e.code(), e.message.clone(), e.line, e.column).with_end_column(e.end_column)
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(e: &RuntimeError) -> Self {
+        let message = e.kind.message();
+        let mut diagnostic = Diagnostic {
+            help: help_for(&message),
+            ..Diagnostic::from_span(e.kind.code(), message, e.span)
+        };
+        // En un desbordamiento de pila solo interesan las llamadas más recientes
+        for frame in e.call_stack.iter().take(SHOWN_FRAMES) {
+            diagnostic = diagnostic.with_note(Message::new(
+                MessageId::CalledFrom,
+                vec![
+                    frame.function.clone(),
+                    frame.call_site.start.line.to_string(),
+                    frame.call_site.start.column.to_string(),
+                ],
+            ));
+        }
+        let omitted = e.call_stack.len().saturating_sub(SHOWN_FRAMES);
+        if omitted > 0 {
+            diagnostic = diagnostic.with_note(Message::new(
+                MessageId::MoreCalls,
+                vec![omitted.to_string()],
+            ));
+        }
+        diagnostic
+    }
+}
@@ -1,13 +1,111 @@
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use crate::{
     core::{
-        ast::{Expr, Stmt},
+        ast::{Expr, Span, Stmt},
+        io::{Io, StdIo},
+        messages::{Arg, Message, MessageId},
         token::Token,
+        type_checker::FunctionSignature,
     },
     persistence::models::SyntaxConfig,
 };
 
+// Profundidad de llamadas por defecto; `compiler.toml` puede cambiarla
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 20_000;
+
+// Cada cuántos pasos se consultan el reloj y la cancelación
+const CHECK_INTERVAL: u64 = 1024;
+
+// Presupuestos para ejecutar código no confiable; `None` significa sin límite
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionLimits {
+    // Sentencias y expresiones evaluadas
+    pub max_steps: Option<u64>,
+    pub timeout: Option<Duration>,
+    // Longitud en bytes de cualquier cadena
+    pub max_string_length: Option<usize>,
+    // Variables vivas a la vez, sumando todos los ámbitos
+    pub max_values: Option<usize>,
+}
+
+// Permite detener desde otro hilo una ejecución en curso
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Pila nativa estimada por cada nivel de llamada del intérprete; sin
+// optimizaciones cada nivel ocupa varias veces más
+const STACK_PER_CALL: usize = if cfg!(debug_assertions) {
+    24 * 1024
+} else {
+    4 * 1024
+};
+const MIN_STACK_SIZE: usize = 2 * 1024 * 1024;
+const MAX_STACK_SIZE: usize = 1024 * 1024 * 1024;
+// Pila que se deja libre al final para informar el desbordamiento
+const STACK_RED_ZONE: usize = 256 * 1024;
+
+thread_local! {
+    // Inicio y tamaño utilizable de la pila del hilo creado por `with_call_stack`
+    static CALL_STACK: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+// Las expresiones muy anidadas gastan más pila por llamada que la estimada
+fn stack_exhausted() -> bool {
+    CALL_STACK
+        .get()
+        .is_some_and(|(start, usable)| start.abs_diff(stack_address()) > usable)
+}
+
+// El intérprete recorre el árbol de forma recursiva: el anfitrión crea el
+// intérprete y lo usa dentro de `f`, que corre en un hilo cuya pila alcanza
+// para `max_call_depth` llamadas anidadas
+pub fn with_call_stack<T: Send>(
+    max_call_depth: usize,
+    f: impl FnOnce() -> T + Send,
+) -> Result<T, RuntimeError> {
+    let stack_size = max_call_depth
+        .saturating_mul(STACK_PER_CALL)
+        .clamp(MIN_STACK_SIZE, MAX_STACK_SIZE);
+    std::thread::scope(|scope| {
+        let thread = std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn_scoped(scope, move || {
+                CALL_STACK.set(Some((stack_address(), stack_size - STACK_RED_ZONE)));
+                f()
+            })
+            .map_err(|_| {
+                Interpreter::error_at(
+                    RuntimeErrorKind::StackUnavailable { size: stack_size },
+                    Span::default(),
+                )
+            })?;
+        Ok(thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+    })
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Int(i32),
@@ -20,12 +118,28 @@ pub enum Value {
     Void,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Variable {
     pub var_type: Token,
     pub value: Value,
 }
 
+impl Value {
+    // Tipo del valor, para mostrarlo en los errores
+    pub fn type_token(&self) -> Token {
+        match self {
+            Value::Int(_) => Token::IntType,
+            Value::Float(_) => Token::FloatType,
+            Value::BigInt(_) => Token::BigIntType,
+            Value::Double(_) => Token::DoubleType,
+            Value::String(_) => Token::StringType,
+            Value::Char(_) => Token::CharType,
+            Value::Bool(_) => Token::BoolType,
+            Value::Void => Token::VoidType,
+        }
+    }
+}
+
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -41,248 +155,775 @@ impl std::fmt::Display for Value {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    UndefinedVariable(String),
+    UndefinedFunction(String),
+    MissingInitializer(String),
+    TypeMismatch {
+        expected: Token,
+        found: Value,
+    },
+    DivisionByZero,
+    ArityMismatch {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    Overflow,
+    InvalidOperand {
+        operator: String,
+        value: Value,
+    },
+    IncompatibleOperands {
+        operator: String,
+        left: Value,
+        right: Value,
+    },
+    MissingReturn {
+        function: String,
+        expected: Token,
+    },
+    StackOverflow {
+        limit: usize,
+    },
+    // La pila nativa se agotó antes de llegar a `max_call_depth`
+    NativeStackExhausted {
+        depth: usize,
+    },
+    // No se pudo crear el hilo de `with_call_stack`
+    StackUnavailable {
+        size: usize,
+    },
+    LimitExceeded(Limit),
+    Cancelled,
+    EndOfInput,
+    Io(String),
+    // Error informado por una función nativa del anfitrión
+    Native(String),
+}
+
+impl From<std::io::Error> for RuntimeErrorKind {
+    fn from(e: std::io::Error) -> Self {
+        RuntimeErrorKind::Io(e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Limit {
+    Steps(u64),
+    Time(Duration),
+    StringLength(usize),
+    Values(usize),
+}
+
+impl RuntimeErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeErrorKind::UndefinedVariable(_) => "E501",
+            RuntimeErrorKind::UndefinedFunction(_) => "E502",
+            RuntimeErrorKind::TypeMismatch { .. } => "E503",
+            RuntimeErrorKind::DivisionByZero => "E504",
+            RuntimeErrorKind::ArityMismatch { .. } => "E505",
+            RuntimeErrorKind::Overflow => "E506",
+            RuntimeErrorKind::InvalidOperand { .. } => "E507",
+            RuntimeErrorKind::IncompatibleOperands { .. } => "E508",
+            RuntimeErrorKind::StackOverflow { .. } => "E509",
+            RuntimeErrorKind::LimitExceeded(_) => "E510",
+            RuntimeErrorKind::Cancelled => "E511",
+            RuntimeErrorKind::EndOfInput => "E512",
+            RuntimeErrorKind::Io(_) => "E513",
+            RuntimeErrorKind::Native(_) => "E514",
+            RuntimeErrorKind::MissingInitializer(_) => "E515",
+            RuntimeErrorKind::NativeStackExhausted { .. } => "E516",
+            RuntimeErrorKind::StackUnavailable { .. } => "E517",
+            RuntimeErrorKind::MissingReturn { .. } => "E518",
+        }
+    }
+
+    pub fn message(&self) -> Message {
+        match self {
+            RuntimeErrorKind::UndefinedVariable(name) => {
+                Message::new(MessageId::UndefinedVariable, vec![name.clone()])
+            }
+            RuntimeErrorKind::UndefinedFunction(name) => {
+                Message::new(MessageId::UndefinedFunction, vec![name.clone()])
+            }
+            RuntimeErrorKind::MissingInitializer(name) => {
+                Message::new(MessageId::MissingInitializer, vec![name.clone()])
+            }
+            RuntimeErrorKind::TypeMismatch { expected, found } => Message::new(
+                MessageId::TypeMismatch,
+                vec![Arg::Word(expected.clone()), Arg::Word(found.type_token())],
+            ),
+            RuntimeErrorKind::DivisionByZero => MessageId::DivisionByZero.into(),
+            RuntimeErrorKind::ArityMismatch {
+                function,
+                expected,
+                found,
+            } => Message::new(
+                MessageId::ArityMismatch,
+                vec![function.clone(), expected.to_string(), found.to_string()],
+            ),
+            RuntimeErrorKind::Overflow => MessageId::Overflow.into(),
+            RuntimeErrorKind::InvalidOperand { operator, value } => Message::new(
+                MessageId::InvalidOperand,
+                vec![Arg::from(operator.clone()), Arg::Word(value.type_token())],
+            ),
+            RuntimeErrorKind::IncompatibleOperands {
+                operator,
+                left,
+                right,
+            } => Message::new(
+                MessageId::IncompatibleOperands,
+                vec![
+                    Arg::Word(left.type_token()),
+                    Arg::from(operator.clone()),
+                    Arg::Word(right.type_token()),
+                ],
+            ),
+            RuntimeErrorKind::StackOverflow { limit } => {
+                Message::new(MessageId::StackOverflow, vec![limit.to_string()])
+            }
+            RuntimeErrorKind::MissingReturn { function, expected } => Message::new(
+                MessageId::MissingReturn,
+                vec![Arg::from(function.clone()), Arg::Word(expected.clone())],
+            ),
+            RuntimeErrorKind::NativeStackExhausted { depth } => {
+                Message::new(MessageId::NativeStackExhausted, vec![depth.to_string()])
+            }
+            RuntimeErrorKind::StackUnavailable { size } => Message::new(
+                MessageId::StackUnavailable,
+                vec![(size / (1024 * 1024)).to_string()],
+            ),
+            RuntimeErrorKind::LimitExceeded(Limit::Steps(max)) => {
+                Message::new(MessageId::StepLimit, vec![max.to_string()])
+            }
+            RuntimeErrorKind::LimitExceeded(Limit::Time(timeout)) => Message::new(
+                MessageId::TimeLimit,
+                vec![format!("{} ms", timeout.as_millis())],
+            ),
+            RuntimeErrorKind::LimitExceeded(Limit::StringLength(max)) => {
+                Message::new(MessageId::StringLimit, vec![max.to_string()])
+            }
+            RuntimeErrorKind::LimitExceeded(Limit::Values(max)) => {
+                Message::new(MessageId::ValueLimit, vec![max.to_string()])
+            }
+            RuntimeErrorKind::Cancelled => MessageId::Cancelled.into(),
+            RuntimeErrorKind::EndOfInput => MessageId::EndOfInput.into(),
+            RuntimeErrorKind::Io(message) => {
+                Message::new(MessageId::IoError, vec![message.clone()])
+            }
+            RuntimeErrorKind::Native(message) => {
+                Message::new(MessageId::NativeError, vec![message.clone()])
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+// Llamada activa en el momento del error: función invocada y sitio de la llamada
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub call_site: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    // En caja para que los `Result` del intérprete sigan siendo pequeños
+    pub kind: Box<RuntimeErrorKind>,
+    pub span: Span,
+    // De la llamada más reciente a la más antigua
+    pub call_stack: Vec<StackFrame>,
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (en {}:{})",
+            self.kind, self.span.start.line, self.span.start.column
+        )?;
+        for frame in &self.call_stack {
+            let called_from = Message::new(
+                MessageId::CalledFrom,
+                vec![
+                    frame.function.clone(),
+                    frame.call_site.start.line.to_string(),
+                    frame.call_site.start.column.to_string(),
+                ],
+            );
+            write!(f, "\n  {}", called_from)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+// Para las funciones nativas: el intérprete completa la posición con la de la llamada
+impl From<RuntimeErrorKind> for RuntimeError {
+    fn from(kind: RuntimeErrorKind) -> Self {
+        Interpreter::error_at(kind, Span::default())
+    }
+}
+
+// Señales de control de flujo, separadas del valor que devuelve una función
+enum Flow {
+    Normal,
+    Return(Value),
+    Break,
+    Continue,
+}
+
 pub struct Environment {
     values: HashMap<String, Variable>,
-    parent: Option<Box<Environment>>,
+    parent: Option<Rc<RefCell<Environment>>>,
+    // Variables vivas en este ámbito y sus descendientes, compartido con la raíz
+    live_values: Rc<Cell<usize>>,
 }
 
 impl Environment {
+    pub fn new(parent: Option<Rc<RefCell<Environment>>>) -> Rc<RefCell<Environment>> {
+        let live_values = parent
+            .as_ref()
+            .map(|p| p.borrow().live_values.clone())
+            .unwrap_or_default();
+        Rc::new(RefCell::new(Environment {
+            values: HashMap::new(),
+            parent,
+            live_values,
+        }))
+    }
+
+    pub fn live_values(&self) -> usize {
+        self.live_values.get()
+    }
+
     pub fn get(&self, name: &str) -> Option<Variable> {
         match self.values.get(name) {
             Some(val) => Some(val.clone()),
             None => {
                 if let Some(ref parent) = self.parent {
-                    parent.get(name)
+                    parent.borrow().get(name)
                 } else {
                     None
                 }
             }
         }
     }
+
     pub fn insert(&mut self, name: String, value: Variable) {
-        self.values.insert(name, value);
+        if self.values.insert(name, value).is_none() {
+            self.live_values.set(self.live_values.get() + 1);
+        }
+    }
+
+    pub fn assign(&mut self, name: &str, value: Variable) -> bool {
+        if self.values.contains_key(name) {
+            self.values.insert(name.to_string(), value);
+            return true;
+        }
+        match self.parent {
+            Some(ref parent) => parent.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        self.live_values
+            .set(self.live_values.get() - self.values.len());
     }
 }
 
+// Una función recuerda el ámbito donde fue declarada (ámbito léxico)
+struct Function {
+    return_type: Token,
+    params: Vec<(Token, String)>,
+    body: Vec<Stmt>,
+    closure: Rc<RefCell<Environment>>,
+    span: Span,
+}
+
+// Función del programa anfitrión; recibe los argumentos ya comprobados
+pub type NativeFunction = dyn Fn(&[Value]) -> Result<Value, RuntimeError>;
+
+enum Callable {
+    Script(Function),
+    Native {
+        signature: FunctionSignature,
+        body: Box<NativeFunction>,
+    },
+}
+
+// Las funciones nativas no tienen nombres de parámetros
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub return_type: Token,
+    pub params: Vec<(Token, String)>,
+    pub native: bool,
+}
+
 pub struct Interpreter {
-    environment: Environment,
-    functions: HashMap<String, (Vec<(Token, String)>, Vec<Stmt>)>,
+    environment: Rc<RefCell<Environment>>,
+    functions: HashMap<String, Rc<Callable>>,
+    call_depth: usize,
+    max_call_depth: usize,
+    // Tipo de retorno de cada función en curso, la más reciente al final
+    return_types: Vec<Token>,
+    limits: ExecutionLimits,
+    steps: u64,
+    deadline: Option<Instant>,
+    cancel: CancelHandle,
+    io: Box<dyn Io>,
     pub config: SyntaxConfig,
 }
 
 impl Interpreter {
     pub fn new(config: SyntaxConfig) -> Self {
         Self {
-            environment: Environment {
-                values: HashMap::new(),
-                parent: None,
-            },
+            environment: Environment::new(None),
             functions: HashMap::new(),
-            config: config,
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            return_types: Vec::new(),
+            limits: ExecutionLimits::default(),
+            steps: 0,
+            deadline: None,
+            cancel: CancelHandle::default(),
+            io: Box::new(StdIo),
+            config,
         }
     }
 
-    pub fn interpret(&mut self, statements: Vec<Stmt>) {
-        for stmt in statements {
-            if let Err(e) = self.execute(stmt) {
-                println!("Error en ejecución: {}", e);
-                continue;
-            }
-        }
+    pub fn set_limits(&mut self, limits: ExecutionLimits) {
+        self.limits = limits;
     }
 
-    fn update_variable(&mut self, name: &str, value: Variable) -> bool {
-        let mut current = Some(&mut self.environment);
-        while let Some(env) = current {
-            if env.values.contains_key(name) {
-                env.values.insert(name.to_string(), value);
-                return true;
-            }
-            match env.parent {
-                Some(ref mut next) => current = Some(next),
-                None => break,
+    // Una declaración del programa con el mismo nombre la reemplaza
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        signature: FunctionSignature,
+        body: impl Fn(&[Value]) -> Result<Value, RuntimeError> + 'static,
+    ) {
+        self.functions.insert(
+            name.to_string(),
+            Rc::new(Callable::Native {
+                signature,
+                body: Box::new(body),
+            }),
+        );
+    }
+
+    pub fn set_io(&mut self, io: impl Io + 'static) {
+        self.io = Box::new(io);
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    // Para llegar al límite sin agotar la pila nativa, usar el intérprete dentro de `with_call_stack`
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<(), RuntimeError> {
+        self.run(|interpreter| {
+            for stmt in &statements {
+                interpreter.execute(stmt)?;
             }
-        }
-        false
+            Ok(())
+        })
+    }
+
+    // Evalúa una expresión suelta sobre el estado actual, como hace el REPL
+    pub fn evaluate_expression(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        self.run(|interpreter| interpreter.evaluate(expr))
+    }
+
+    // Los presupuestos de pasos y tiempo se renuevan en cada ejecución
+    fn run<T>(
+        &mut self,
+        body: impl FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    ) -> Result<T, RuntimeError> {
+        self.steps = 0;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+        let result = body(self);
+        let flushed = self.io.flush();
+        let value = result?;
+        flushed.map_err(|e| Self::error_at(e.into(), Span::default()))?;
+        Ok(value)
+    }
+
+    // Llama desde el anfitrión a una función del programa (o nativa) ya declarada
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let callable = self.lookup(name, Span::default())?;
+        self.run(|interpreter| interpreter.invoke(name, &callable, args, &[], Span::default()))
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.environment.borrow().get(name).map(|var| var.value)
+    }
+
+    // Solo cambia variables existentes y respetando su tipo declarado
+    pub fn set_global(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
+        let var_type = self
+            .environment
+            .borrow()
+            .get(name)
+            .map(|var| var.var_type)
+            .ok_or_else(|| {
+                Self::error_at(
+                    RuntimeErrorKind::UndefinedVariable(name.to_string()),
+                    Span::default(),
+                )
+            })?;
+        Self::type_check(&var_type, &value).map_err(|e| Self::error_at(e, Span::default()))?;
+        self.environment
+            .borrow_mut()
+            .assign(name, Variable { var_type, value });
+        Ok(())
+    }
+
+    pub fn define_global(
+        &mut self,
+        name: &str,
+        var_type: Token,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        Self::type_check(&var_type, &value).map_err(|e| Self::error_at(e, Span::default()))?;
+        self.environment
+            .borrow_mut()
+            .insert(name.to_string(), Variable { var_type, value });
+        Ok(())
     }
 
-    fn execute(&mut self, stmt: Stmt) -> Result<Option<Variable>, String> {
+    // Variables del ámbito global, ordenadas por nombre
+    pub fn globals(&self) -> Vec<(String, Variable)> {
+        let environment = self.environment.borrow();
+        let mut globals: Vec<_> = environment
+            .values
+            .iter()
+            .map(|(name, var)| (name.clone(), var.clone()))
+            .collect();
+        globals.sort_by(|a, b| a.0.cmp(&b.0));
+        globals
+    }
+
+    pub fn functions(&self) -> Vec<FunctionInfo> {
+        let mut functions: Vec<_> = self
+            .functions
+            .iter()
+            .map(|(name, callable)| match &**callable {
+                Callable::Script(function) => FunctionInfo {
+                    name: name.clone(),
+                    return_type: function.return_type.clone(),
+                    params: function.params.clone(),
+                    native: false,
+                },
+                Callable::Native { signature, .. } => FunctionInfo {
+                    name: name.clone(),
+                    return_type: signature.return_type.clone(),
+                    params: signature
+                        .params
+                        .iter()
+                        .map(|ty| (ty.clone(), String::new()))
+                        .collect(),
+                    native: true,
+                },
+            })
+            .collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        functions
+    }
+
+    fn execute_in_scope(
+        &mut self,
+        statements: &[Stmt],
+        scope: Rc<RefCell<Environment>>,
+    ) -> Result<Flow, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, scope);
+        let result = self.execute_function_body(statements);
+        self.environment = previous;
+        result
+    }
+
+    // Los brazos con más locales viven en funciones aparte para que cada
+    // nivel de recursión ocupe poca pila nativa
+    fn execute(&mut self, stmt: &Stmt) -> Result<Flow, RuntimeError> {
+        self.tick().map_err(|e| Self::error_at(e, stmt.span()))?;
         match stmt {
             Stmt::VarDeclaration {
                 ty,
                 name,
                 initializer,
-                ..
+                span,
             } => {
-                let value = self.evaluate(initializer.expect("Variable sin inicializador"))?;
-                self.type_check(&ty, &value)?;
-                self.environment.insert(
-                    name,
-                    Variable {
-                        var_type: ty,
-                        value,
-                    },
-                );
-                Ok(None)
-            }
-
-            Stmt::Assignment { name, value } => {
-                let new_val = self.evaluate(value)?;
-                if let Some(var) = self.environment.get(&name) {
-                    self.type_check(&var.var_type, &new_val)?;
-                    self.update_variable(
-                        &name,
-                        Variable {
-                            var_type: var.var_type,
-                            value: new_val,
-                        },
-                    );
-                    Ok(None)
-                } else {
-                    return Err(format!("Variable '{}' no definida", name));
-                }
+                // El parser acepta `int x;`; sin verificación de tipos llega hasta aquí
+                let Some(initializer) = initializer else {
+                    return Err(Self::error_at(
+                        RuntimeErrorKind::MissingInitializer(name.clone()),
+                        *span,
+                    ));
+                };
+                self.declare_variable(ty, name, initializer, *span)?;
+                Ok(Flow::Normal)
+            }
+            Stmt::Assignment { name, value, span } => {
+                self.assign_variable(name, value, *span)?;
+                Ok(Flow::Normal)
             }
-
             Stmt::If {
                 condition,
                 then_branch,
                 else_branch,
+                ..
             } => {
-                let condition_val = self.evaluate(condition)?;
-                if self.is_truthy(condition_val) {
-                    for stmt in then_branch {
-                        let res = self.execute(stmt)?;
-                        if res.is_some() {
-                            return Ok(res);
-                        }
-                    }
+                if Self::is_truthy(self.evaluate(condition)?) {
+                    self.execute_block(then_branch)
                 } else if let Some(branch) = else_branch {
-                    for stmt in branch {
-                        let res = self.execute(stmt)?;
-                        if res.is_some() {
-                            return Ok(res);
-                        }
-                    }
-                }
-                Ok(None)
-            }
-            Stmt::Print { value } => {
-                println!("{}", self.evaluate_strings(value)?);
-                Ok(None)
-            }
-            Stmt::Println { value } => {
-                println!("{}", self.evaluate_strings(value)?);
-                Ok(None)
-            }
-            Stmt::Expression(expr) => {
-                self.evaluate(expr);
-                Ok(None)
-            }
-            Stmt::Return(value) => {
-                if let Some(val_expr) = value {
-                    let val = self.evaluate(val_expr)?;
-                    return Ok(Some(Variable {
-                        var_type: Token::VoidType,
-                        value: val,
-                    }));
-                }
-                Ok(Some(Variable {
-                    var_type: Token::VoidType,
-                    value: Value::Void,
-                }))
-            }
-
-            Stmt::Block(statements) => {
-                let old_env = std::mem::replace(
-                    &mut self.environment,
-                    Environment {
-                        values: HashMap::new(),
-                        parent: None,
-                    },
-                );
-                self.environment.parent = Some(Box::new(old_env));
-                let mut block_result = Ok(None);
-                for stmt in statements {
-                    let result = self.execute(stmt);
-                    if let Ok(Some(_)) = result {
-                        block_result = result;
-                        break;
-                    }
-                    if result.is_err() {
-                        block_result = result;
-                        break;
-                    }
+                    self.execute_block(branch)
+                } else {
+                    Ok(Flow::Normal)
                 }
-                if let Some(parent_env) = self.environment.parent.take() {
-                    self.environment = *parent_env;
+            }
+            Stmt::Print { value, span } => {
+                let text = self.evaluate_strings(value)?;
+                // Sin salto de línea: se vacía para que el texto se vea antes de un `input()`
+                self.io
+                    .write(&text)
+                    .and_then(|_| self.io.flush())
+                    .map_err(|e| Self::error_at(e.into(), *span))?;
+                Ok(Flow::Normal)
+            }
+            Stmt::Println { value, span } => {
+                let text = self.evaluate_strings(value)?;
+                self.io
+                    .write(&format!("{}\n", text))
+                    .map_err(|e| Self::error_at(e.into(), *span))?;
+                Ok(Flow::Normal)
+            }
+            Stmt::Expression { expr, .. } => {
+                self.evaluate(expr)?;
+                Ok(Flow::Normal)
+            }
+            Stmt::Return { value, span } => {
+                let value = match value {
+                    Some(val_expr) => self.evaluate(val_expr)?,
+                    None => Value::Void,
+                };
+                // Un return de nivel superior solo termina la sentencia actual
+                match self.return_types.last() {
+                    Some(return_type) => Self::check_return(return_type, value)
+                        .map(Flow::Return)
+                        .map_err(|e| Self::error_at(e, *span)),
+                    None => Ok(Flow::Return(value)),
                 }
-
-                block_result
             }
-
+            Stmt::Block { statements, .. } => self.execute_block(statements),
             Stmt::Function {
-                return_type: _,
+                return_type,
                 name,
                 params,
                 body,
+                span,
             } => {
-                self.functions.insert(name, (params, body));
-                Ok(None)
-            }
-            Stmt::While { condition, body } => {
-                while {
-                    let cond_val = self.evaluate(condition.clone())?;
-                    self.is_truthy(cond_val)
-                } {
-                    for stmt in body.clone() {
-                        match self.execute(stmt) {
-                            Ok(Some(v)) => return Ok(Some(v)),
-                            Ok(None) => {}
-                            Err(e) => {
-                                println!("Error en ejecución: {}", e);
-                                break;
-                            }
-                        }
-                    }
-                }
-                Ok(None)
+                self.declare_function(return_type, name, params, body, *span);
+                Ok(Flow::Normal)
+            }
+            Stmt::While {
+                condition, body, ..
+            } => self.execute_while(condition, body),
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                // La variable de control vive en un ámbito propio que envuelve al bucle
+                let scope = Environment::new(Some(self.environment.clone()));
+                let previous = std::mem::replace(&mut self.environment, scope);
+                let result = self.execute_for(
+                    initializer.as_deref(),
+                    condition.as_ref(),
+                    increment.as_deref(),
+                    body,
+                );
+                self.environment = previous;
+                result
             }
-
             Stmt::Switch {
                 condition,
                 cases,
                 default,
-            } => {
-                let val = self.evaluate(condition)?;
-                let mut executed = false;
-
-                for (case_expr, body) in cases {
-                    let case_val = self.evaluate(case_expr)?;
-                    if val == case_val {
-                        let res = self.execute_function_body(body)?;
-                        if res.is_some() {
-                            return Ok(res);
-                        }
-                        executed = true;
-                        break;
-                    }
-                }
-                if !executed {
-                    if let Some(default_body) = default {
-                        let res = self.execute_function_body(default_body)?;
-                        if res.is_some() {
-                            return Ok(res);
-                        }
-                    }
-                }
-                Ok(None)
+                ..
+            } => self.execute_switch(condition, cases, default.as_deref()),
+            Stmt::Break { .. } => Ok(Flow::Break),
+            Stmt::Continue { .. } => Ok(Flow::Continue),
+        }
+    }
+
+    fn declare_variable(
+        &mut self,
+        ty: &Token,
+        name: &str,
+        initializer: &Expr,
+        span: Span,
+    ) -> Result<(), RuntimeError> {
+        let value = self.evaluate(initializer)?;
+        Self::type_check(ty, &value).map_err(|e| Self::error_at(e, span))?;
+        self.environment.borrow_mut().insert(
+            name.to_string(),
+            Variable {
+                var_type: ty.clone(),
+                value,
+            },
+        );
+        self.check_values().map_err(|e| Self::error_at(e, span))
+    }
+
+    fn assign_variable(
+        &mut self,
+        name: &str,
+        value: &Expr,
+        span: Span,
+    ) -> Result<(), RuntimeError> {
+        let new_val = self.evaluate(value)?;
+        let current = self.environment.borrow().get(name);
+        let Some(var) = current else {
+            return Err(Self::error_at(
+                RuntimeErrorKind::UndefinedVariable(name.to_string()),
+                span,
+            ));
+        };
+        Self::type_check(&var.var_type, &new_val).map_err(|e| Self::error_at(e, span))?;
+        self.environment.borrow_mut().assign(
+            name,
+            Variable {
+                var_type: var.var_type,
+                value: new_val,
+            },
+        );
+        Ok(())
+    }
+
+    fn declare_function(
+        &mut self,
+        return_type: &Token,
+        name: &str,
+        params: &[(Token, String)],
+        body: &[Stmt],
+        span: Span,
+    ) {
+        self.functions.insert(
+            name.to_string(),
+            Rc::new(Callable::Script(Function {
+                return_type: return_type.clone(),
+                params: params.to_vec(),
+                body: body.to_vec(),
+                closure: self.environment.clone(),
+                span,
+            })),
+        );
+    }
+
+    fn execute_while(&mut self, condition: &Expr, body: &[Stmt]) -> Result<Flow, RuntimeError> {
+        while Self::is_truthy(self.evaluate(condition)?) {
+            match self.execute_block(body)? {
+                Flow::Break => return Ok(Flow::Normal),
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Normal | Flow::Continue => {}
             }
+        }
+        Ok(Flow::Normal)
+    }
 
-            _ => Ok(None),
+    fn execute_switch(
+        &mut self,
+        condition: &Expr,
+        cases: &[(Expr, Vec<Stmt>)],
+        default: Option<&[Stmt]>,
+    ) -> Result<Flow, RuntimeError> {
+        let val = self.evaluate(condition)?;
+        let mut selected = default;
+
+        for (case_expr, body) in cases {
+            let case_val = self.evaluate(case_expr)?;
+            if val == case_val {
+                selected = Some(body);
+                break;
+            }
+        }
+        let Some(body) = selected else {
+            return Ok(Flow::Normal);
+        };
+        // `break` termina el switch; `continue` y `return` siguen subiendo
+        match self.execute_block(body)? {
+            Flow::Break => Ok(Flow::Normal),
+            flow => Ok(flow),
         }
     }
 
-    fn type_check(&self, expected: &Token, value: &Value) -> Result<(), String> {
+    fn tick(&mut self) -> Result<(), RuntimeErrorKind> {
+        self.steps += 1;
+        if let Some(max) = self.limits.max_steps
+            && self.steps > max
+        {
+            return Err(RuntimeErrorKind::LimitExceeded(Limit::Steps(max)));
+        }
+        if !self.steps.is_multiple_of(CHECK_INTERVAL) {
+            return Ok(());
+        }
+        if self.cancel.is_cancelled() {
+            return Err(RuntimeErrorKind::Cancelled);
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout)
+            && Instant::now() >= deadline
+        {
+            return Err(RuntimeErrorKind::LimitExceeded(Limit::Time(timeout)));
+        }
+        Ok(())
+    }
+
+    fn check_string(&self, value: Value) -> Result<Value, RuntimeErrorKind> {
+        match (&value, self.limits.max_string_length) {
+            (Value::String(s), Some(max)) if s.len() > max => {
+                Err(RuntimeErrorKind::LimitExceeded(Limit::StringLength(max)))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    fn check_values(&self) -> Result<(), RuntimeErrorKind> {
+        match self.limits.max_values {
+            Some(max) if self.environment.borrow().live_values() > max => {
+                Err(RuntimeErrorKind::LimitExceeded(Limit::Values(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn error_at(kind: RuntimeErrorKind, span: Span) -> RuntimeError {
+        RuntimeError {
+            kind: Box::new(kind),
+            span,
+            call_stack: Vec::new(),
+        }
+    }
+
+    pub(crate) fn type_check(expected: &Token, value: &Value) -> Result<(), RuntimeErrorKind> {
         match (expected, value) {
             (Token::IntType, Value::Int(_)) => Ok(()),
             (Token::FloatType, Value::Float(_)) => Ok(()),
@@ -291,190 +932,417 @@ impl Interpreter {
             (Token::StringType, Value::String(_)) => Ok(()),
             (Token::CharType, Value::Char(_)) => Ok(()),
 
-            _ => Err(format!(
-                "Error de tipo: se esperaba {:?} pero se recibió {:?}",
-                expected, value
-            )),
+            _ => Err(RuntimeErrorKind::TypeMismatch {
+                expected: expected.clone(),
+                found: value.clone(),
+            }),
         }
     }
 
-    fn evaluate(&mut self, expr: Expr) -> Result<Value, String> {
+    fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        self.tick().map_err(|e| Self::error_at(e, expr.span()))?;
         match expr {
-            Expr::Unary { operator, right } => {
-                let val = self.evaluate(*right)?;
-                match operator {
-                    Token::Minus => match val {
-                        Value::Int(n) => Ok(Value::Int(-n)),
-                        Value::Float(f) => Ok(Value::Float(-f)),
-                        Value::Double(d) => Ok(Value::Double(-d)),
-                        _ => Err(format!("Operador '-' no aplicable a {:?}", val)),
-                    },
-                    Token::Not => match val {
-                        Value::Bool(b) => Ok(Value::Bool(!b)),
-                        _ => Err(format!("Operador '!' no aplicable a {:?}", val)),
-                    },
-                    _ => Err(format!("Operador unario desconocido {:?}", operator)),
-                }
-            }
-            Expr::Literal(token) => {
-                let value = match token {
-                    Token::IntegerLiteral(n) => Value::Int(n as i32),
-                    Token::FloatLiteral(n) => Value::Float(n as f32),
-                    Token::StringLiteral(s) => Value::String(s),
-                    Token::CharLiteral(c) => Value::Char(c),
-                    Token::BoolLiteral(b) => Value::Bool(b),
-                    _ => Value::Void,
-                };
-                Ok(value)
+            Expr::Unary {
+                operator,
+                right,
+                span,
+            } => {
+                let val = self.evaluate(right)?;
+                Self::execute_unary_op(operator, val).map_err(|e| Self::error_at(e, *span))
             }
-            Expr::Variable(name) => self
+            Expr::Literal { value: token, .. } => Ok(Self::literal_value(token.clone())),
+            Expr::Variable { name, span } => self
                 .environment
-                .get(&name)
+                .borrow()
+                .get(name)
                 .map(|v| v.value)
-                .ok_or_else(|| format!("Variable no definida: {}", name)),
+                .ok_or_else(|| {
+                    Self::error_at(RuntimeErrorKind::UndefinedVariable(name.clone()), *span)
+                }),
             Expr::Binary {
                 left,
                 operator,
                 right,
+                span,
             } => {
-                let l = self.evaluate(*left)?;
-                let r = self.evaluate(*right)?;
-                Ok(self.execute_binary_op(l, operator, r))
-            }
-            Expr::Call { callee, arguments } => {
-                let (params, body) = self
-                    .functions
-                    .get(&callee)
-                    .cloned()
-                    .ok_or_else(|| format!("Función no definida: {}", callee))?;
-
-                let mut args_values = Vec::new();
-                for arg in arguments {
-                    args_values.push(self.evaluate(arg)?);
-                }
+                let l = self.evaluate(left)?;
+                let r = self.evaluate(right)?;
+                Self::execute_binary_op(l, operator.clone(), r)
+                    .and_then(|value| self.check_string(value))
+                    .map_err(|e| Self::error_at(e, *span))
+            }
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => self.evaluate_logical(left, operator, right),
+            Expr::Call {
+                callee,
+                arguments,
+                span,
+            } => self.call_function(callee, arguments, *span),
+            Expr::Input { span } => self.read_input().map_err(|e| Self::error_at(e, *span)),
+        }
+    }
 
-                let previous_env = std::mem::replace(
-                    &mut self.environment,
-                    Environment {
-                        values: HashMap::new(),
-                        parent: None,
-                    },
-                );
-                self.environment.parent = Some(Box::new(previous_env));
-
-                for (i, (ty, name)) in params.iter().enumerate() {
-                    self.environment.insert(
-                        name.clone(),
-                        Variable {
-                            var_type: ty.clone(),
-                            value: args_values[i].clone(),
-                        },
-                    );
-                }
+    fn read_input(&mut self) -> Result<Value, RuntimeErrorKind> {
+        // Lo escrito hasta ahora suele ser la pregunta que se está respondiendo
+        self.io.flush()?;
+        let line = self.io.read_line()?.ok_or(RuntimeErrorKind::EndOfInput)?;
+        self.check_string(Self::parse_input(&line, &self.config))
+    }
 
-                let result = self.execute_function_body(body);
+    fn evaluate_logical(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Value, RuntimeError> {
+        let l = self.evaluate(left)?;
+        Self::type_check(&Token::BoolType, &l).map_err(|e| Self::error_at(e, left.span()))?;
+        // Cortocircuito: el resultado ya está decidido por el operando izquierdo
+        let decided = match operator {
+            Token::And => l == Value::Bool(false),
+            _ => l == Value::Bool(true),
+        };
+        if decided {
+            return Ok(l);
+        }
+        let r = self.evaluate(right)?;
+        Self::type_check(&Token::BoolType, &r).map_err(|e| Self::error_at(e, right.span()))?;
+        Ok(r)
+    }
 
-                if let Some(parent_env) = self.environment.parent.take() {
-                    self.environment = *parent_env;
-                }
+    fn call_function(
+        &mut self,
+        callee: &str,
+        arguments: &[Expr],
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let callable = self.lookup(callee, span)?;
+        let mut args_values = Vec::new();
+        for arg in arguments {
+            args_values.push(self.evaluate(arg)?);
+        }
+        let arg_spans: Vec<Span> = arguments.iter().map(Expr::span).collect();
+        self.invoke(callee, &callable, args_values, &arg_spans, span)
+    }
 
-                match result {
-                    Ok(Some(var)) => Ok(var.value),
-                    Ok(None) => Ok(Value::Void),
-                    Err(e) => Err(e),
-                }
-            }
-            Expr::Input => {
-                let mut input_text = String::new();
-                std::io::stdin().read_line(&mut input_text).unwrap();
-                let trimmed = input_text.trim();
+    fn lookup(&self, name: &str, span: Span) -> Result<Rc<Callable>, RuntimeError> {
+        self.functions.get(name).cloned().ok_or_else(|| {
+            Self::error_at(RuntimeErrorKind::UndefinedFunction(name.to_string()), span)
+        })
+    }
 
-                if trimmed == self.config.literals.r#true {
-                    return Ok(Value::Bool(true));
-                }
-                if trimmed == self.config.literals.r#false {
-                    return Ok(Value::Bool(false));
-                }
+    // Camino común a las llamadas del programa y a las del anfitrión
+    fn invoke(
+        &mut self,
+        name: &str,
+        callable: &Callable,
+        args_values: Vec<Value>,
+        arg_spans: &[Span],
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let param_types: Vec<&Token> = match callable {
+            Callable::Script(function) => function.params.iter().map(|(ty, _)| ty).collect(),
+            Callable::Native { signature, .. } => signature.params.iter().collect(),
+        };
+        if args_values.len() != param_types.len() {
+            return Err(Self::error_at(
+                RuntimeErrorKind::ArityMismatch {
+                    function: name.to_string(),
+                    expected: param_types.len(),
+                    found: args_values.len(),
+                },
+                span,
+            ));
+        }
+        for (i, (ty, value)) in param_types.iter().zip(&args_values).enumerate() {
+            let arg_span = arg_spans.get(i).copied().unwrap_or(span);
+            Self::type_check(ty, value).map_err(|e| Self::error_at(e, arg_span))?;
+        }
+        if self.call_depth >= self.max_call_depth {
+            return Err(Self::error_at(
+                RuntimeErrorKind::StackOverflow {
+                    limit: self.max_call_depth,
+                },
+                span,
+            ));
+        }
+        if stack_exhausted() {
+            return Err(Self::error_at(
+                RuntimeErrorKind::NativeStackExhausted {
+                    depth: self.call_depth,
+                },
+                span,
+            ));
+        }
 
-                if let Ok(n) = trimmed.parse::<i32>() {
-                    return Ok(Value::Int(n));
-                }
+        let result = match callable {
+            Callable::Script(function) => self.call_script(name, function, args_values, span),
+            Callable::Native { signature, body } => {
+                Self::call_native(signature, body, &args_values, span)
+            }
+        };
+        // La traza se arma al propagar el error hacia cada llamador
+        result.map_err(|mut e| {
+            e.call_stack.push(StackFrame {
+                function: name.to_string(),
+                call_site: span,
+            });
+            e
+        })
+    }
 
-                if let Ok(n) = trimmed.parse::<f64>() {
-                    return Ok(Value::Double(n));
-                }
+    fn call_script(
+        &mut self,
+        name: &str,
+        function: &Function,
+        args_values: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        // El cuerpo se ejecuta sobre el ámbito de la declaración, no sobre el del llamador
+        let scope = Environment::new(Some(function.closure.clone()));
+        for ((ty, name), value) in function.params.iter().zip(args_values) {
+            scope.borrow_mut().insert(
+                name.clone(),
+                Variable {
+                    var_type: ty.clone(),
+                    value,
+                },
+            );
+        }
+        self.check_values().map_err(|e| Self::error_at(e, span))?;
 
-                if trimmed.len() == 1 {
-                    return Ok(Value::Char(trimmed.chars().next().unwrap()));
-                }
+        self.call_depth += 1;
+        self.return_types.push(function.return_type.clone());
+        let result = self.execute_in_scope(&function.body, scope);
+        self.return_types.pop();
+        self.call_depth -= 1;
+
+        match result? {
+            Flow::Return(value) => Ok(value),
+            _ if function.return_type == Token::VoidType => Ok(Value::Void),
+            _ => Err(Self::error_at(
+                RuntimeErrorKind::MissingReturn {
+                    function: name.to_string(),
+                    expected: function.return_type.clone(),
+                },
+                function.span,
+            )),
+        }
+    }
 
-                Ok(Value::String(trimmed.to_string()))
+    // El resultado también se comprueba: el programa solo recibe lo que espera
+    fn call_native(
+        signature: &FunctionSignature,
+        body: &NativeFunction,
+        args_values: &[Value],
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let value = body(args_values).map_err(|mut e| {
+            if e.span == Span::default() {
+                e.span = span;
             }
-            _ => Ok(Value::Int(0)),
+            e
+        })?;
+        Self::check_return(&signature.return_type, value).map_err(|e| Self::error_at(e, span))
+    }
+
+    // Una función void solo puede devolver `Value::Void`
+    pub(crate) fn check_return(
+        return_type: &Token,
+        value: Value,
+    ) -> Result<Value, RuntimeErrorKind> {
+        match (return_type, &value) {
+            (Token::VoidType, Value::Void) => Ok(value),
+            (Token::VoidType, _) => Err(RuntimeErrorKind::TypeMismatch {
+                expected: Token::VoidType,
+                found: value,
+            }),
+            (ty, _) => Self::type_check(ty, &value).map(|_| value),
         }
     }
 
-    fn execute_binary_op(&self, left: Value, op: Token, right: Value) -> Value {
-        match (&left, &op, &right) {
-            // --- ARITMÉTICA PARA ENTEROS (i32) ---
-            (Value::Int(a), op, Value::Int(b)) => match op {
-                Token::Plus => Value::Int(a + b),
-                Token::Minus => Value::Int(a - b),
-                Token::Multiply => Value::Int(a * b),
-                Token::Divide => Value::Int(a / b),
-                Token::Modulo => Value::Int(a % b),
-                Token::EqualEqual => Value::Bool(a == b),
-                Token::NotEqual => Value::Bool(a != b),
-                Token::Greater => Value::Bool(a > b),
-                Token::Less => Value::Bool(a < b),
-                _ => panic!("Operador {:?} no soportado para Int", op),
-            },
+    pub(crate) fn parse_input(input_text: &str, config: &SyntaxConfig) -> Value {
+        let trimmed = input_text.trim();
 
-            // --- ARITMÉTICA PARA DOUBLE (f64) Y MIXTA ---
-            (Value::Double(a), op, Value::Double(b)) => match op {
-                Token::Plus => Value::Double(a + b),
-                Token::Minus => Value::Double(a - b),
-                Token::Multiply => Value::Double(a * b),
-                Token::Divide => Value::Double(a / b),
-                Token::EqualEqual => Value::Bool(a == b),
-                _ => todo!("Soportar más operaciones de Double"),
-            },
+        if trimmed == config.literals.r#true {
+            return Value::Bool(true);
+        }
+        if trimmed == config.literals.r#false {
+            return Value::Bool(false);
+        }
 
-            // Promoción automática: Int con Double
-            (Value::Int(a), op, Value::Double(b)) => match op {
-                Token::Plus => Value::Double(*a as f64 + b),
-                Token::Minus => Value::Double(*a as f64 - b),
-                Token::Multiply => Value::Double(*a as f64 * b),
-                Token::EqualEqual => Value::Bool(*a as f64 == *b),
-                _ => panic!("Operación mixta Int/Double no soportada"),
-            },
+        if let Ok(n) = trimmed.parse::<i32>() {
+            return Value::Int(n);
+        }
+
+        if let Ok(n) = trimmed.parse::<f64>() {
+            return Value::Double(n);
+        }
+
+        if trimmed.len() == 1 {
+            return Value::Char(trimmed.chars().next().unwrap());
+        }
+
+        Value::String(trimmed.to_string())
+    }
+
+    pub(crate) fn literal_value(token: Token) -> Value {
+        match token {
+            Token::IntegerLiteral(n) => Value::Int(n as i32),
+            Token::FloatLiteral(n) => Value::Float(n as f32),
+            Token::StringLiteral(s) => Value::String(s),
+            Token::CharLiteral(c) => Value::Char(c),
+            Token::BoolLiteral(b) => Value::Bool(b),
+            _ => Value::Void,
+        }
+    }
 
-            // Promoción automática: Double con Int
-            (Value::Double(a), op, Value::Int(b)) => match op {
-                Token::Plus => Value::Double(a + *b as f64),
-                Token::Minus => Value::Double(a - *b as f64),
-                Token::Multiply => Value::Double(a * *b as f64),
-                Token::EqualEqual => Value::Bool(*a == *b as f64),
-                _ => panic!("Operación mixta Double/Int no soportada"),
+    pub(crate) fn execute_unary_op(
+        operator: &Token,
+        val: Value,
+    ) -> Result<Value, RuntimeErrorKind> {
+        match operator {
+            Token::Minus => match val {
+                Value::Int(n) => n
+                    .checked_neg()
+                    .map(Value::Int)
+                    .ok_or(RuntimeErrorKind::Overflow),
+                Value::Float(f) => Ok(Value::Float(-f)),
+                Value::BigInt(n) => n
+                    .checked_neg()
+                    .map(Value::BigInt)
+                    .ok_or(RuntimeErrorKind::Overflow),
+                Value::Double(d) => Ok(Value::Double(-d)),
+                _ => Err(RuntimeErrorKind::InvalidOperand {
+                    operator: "-".to_string(),
+                    value: val,
+                }),
+            },
+            Token::Not => match val {
+                Value::Bool(b) => Ok(Value::Bool(!b)),
+                _ => Err(RuntimeErrorKind::InvalidOperand {
+                    operator: "!".to_string(),
+                    value: val,
+                }),
             },
+            _ => Err(RuntimeErrorKind::InvalidOperand {
+                operator: operator.symbol().unwrap_or("?").to_string(),
+                value: val,
+            }),
+        }
+    }
 
-            // --- OPERACIONES DE STRINGS ---
-            (Value::String(a), Token::Plus, Value::String(b)) => {
-                Value::String(format!("{}{}", a, b))
+    // Todo par operador/tipos tiene resultado definido o un error tipado
+    pub fn execute_binary_op(
+        left: Value,
+        op: Token,
+        right: Value,
+    ) -> Result<Value, RuntimeErrorKind> {
+        let result = match Self::promote(&left, &right) {
+            Some((Value::Int(a), Value::Int(b))) => {
+                Self::integer_op(a as i64, &op, b as i64, |n| {
+                    i32::try_from(n).ok().map(Value::Int)
+                })?
+            }
+            Some((Value::BigInt(a), Value::BigInt(b))) => {
+                Self::integer_op(a, &op, b, |n| Some(Value::BigInt(n)))?
             }
+            Some((Value::Float(a), Value::Float(b))) => {
+                Self::float_op(a as f64, &op, b as f64, |n| Value::Float(n as f32))
+            }
+            Some((Value::Double(a), Value::Double(b))) => Self::float_op(a, &op, b, Value::Double),
+            _ => match (&left, &op, &right) {
+                (Value::String(a), Token::Plus, Value::String(b)) => {
+                    Some(Value::String(format!("{}{}", a, b)))
+                }
+                (l, Token::EqualEqual, r) => Some(Value::Bool(l == r)),
+                (l, Token::NotEqual, r) => Some(Value::Bool(l != r)),
+                _ => None,
+            },
+        };
+
+        result.ok_or_else(|| RuntimeErrorKind::IncompatibleOperands {
+            operator: op.symbol().unwrap_or("?").to_string(),
+            left,
+            right,
+        })
+    }
 
-            // --- COMPARACIÓN GENÉRICA (Fall-through) ---
-            (l, Token::EqualEqual, r) => Value::Bool(l == r),
-            (l, Token::NotEqual, r) => Value::Bool(l != r),
+    // Promoción numérica: ambos operandos pasan al tipo más amplio (Int < BigInt < Float < Double)
+    fn promote(left: &Value, right: &Value) -> Option<(Value, Value)> {
+        let rank = |value: &Value| match value {
+            Value::Int(_) => Some(0),
+            Value::BigInt(_) => Some(1),
+            Value::Float(_) => Some(2),
+            Value::Double(_) => Some(3),
+            _ => None,
+        };
+        let target = rank(left)?.max(rank(right)?);
+        let widen = |value: &Value| match (value, target) {
+            (Value::Int(n), 1) => Value::BigInt(*n as i64),
+            (Value::Int(n), 2) => Value::Float(*n as f32),
+            (Value::Int(n), 3) => Value::Double(*n as f64),
+            (Value::BigInt(n), 2) => Value::Float(*n as f32),
+            (Value::BigInt(n), 3) => Value::Double(*n as f64),
+            (Value::Float(n), 3) => Value::Double(*n as f64),
+            _ => value.clone(),
+        };
+        Some((widen(left), widen(right)))
+    }
 
-            _ => panic!(
-                "Operación no soportada o tipos incompatibles: {:?} {:?} {:?}",
-                left, op, right
-            ),
+    fn integer_op(
+        a: i64,
+        op: &Token,
+        b: i64,
+        to_value: impl Fn(i64) -> Option<Value>,
+    ) -> Result<Option<Value>, RuntimeErrorKind> {
+        let result = match op {
+            Token::Divide | Token::Modulo if b == 0 => {
+                return Err(RuntimeErrorKind::DivisionByZero);
+            }
+            Token::Plus => a.checked_add(b),
+            Token::Minus => a.checked_sub(b),
+            Token::Multiply => a.checked_mul(b),
+            Token::Divide => a.checked_div(b),
+            Token::Modulo => a.checked_rem(b),
+            _ => return Ok(Self::compare(a.partial_cmp(&b), op)),
+        };
+        result
+            .and_then(to_value)
+            .map(Some)
+            .ok_or(RuntimeErrorKind::Overflow)
+    }
+
+    fn float_op(a: f64, op: &Token, b: f64, to_value: impl Fn(f64) -> Value) -> Option<Value> {
+        match op {
+            Token::Plus => Some(to_value(a + b)),
+            Token::Minus => Some(to_value(a - b)),
+            Token::Multiply => Some(to_value(a * b)),
+            Token::Divide => Some(to_value(a / b)),
+            Token::Modulo => Some(to_value(a % b)),
+            _ => Self::compare(a.partial_cmp(&b), op),
         }
     }
 
-    fn is_truthy(&self, value: Value) -> bool {
+    fn compare(ordering: Option<std::cmp::Ordering>, op: &Token) -> Option<Value> {
+        use std::cmp::Ordering::{Equal, Greater, Less};
+        let result = match op {
+            Token::EqualEqual => ordering == Some(Equal),
+            Token::NotEqual => ordering != Some(Equal),
+            Token::Greater => ordering == Some(Greater),
+            Token::Less => ordering == Some(Less),
+            Token::GreaterEqual => matches!(ordering, Some(Greater | Equal)),
+            Token::LessEqual => matches!(ordering, Some(Less | Equal)),
+            _ => return None,
+        };
+        Some(Value::Bool(result))
+    }
+
+    pub(crate) fn is_truthy(value: Value) -> bool {
         match value {
             Value::Bool(b) => b,
             Value::Int(n) => n != 0,
@@ -483,19 +1351,55 @@ impl Interpreter {
         }
     }
 
-    fn execute_function_body(&mut self, body: Vec<Stmt>) -> Result<Option<Variable>, String> {
+    fn execute_for(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        increment: Option<&Stmt>,
+        body: &[Stmt],
+    ) -> Result<Flow, RuntimeError> {
+        if let Some(init) = initializer {
+            self.execute(init)?;
+        }
+        loop {
+            if let Some(cond) = condition
+                && !Self::is_truthy(self.evaluate(cond)?)
+            {
+                return Ok(Flow::Normal);
+            }
+            match self.execute_block(body)? {
+                Flow::Break => return Ok(Flow::Normal),
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Normal | Flow::Continue => {}
+            }
+            if let Some(inc) = increment {
+                self.execute(inc)?;
+            }
+        }
+    }
+
+    // Los cuerpos de if, bucles y casos abren su propio ámbito, como un bloque
+    fn execute_block(&mut self, statements: &[Stmt]) -> Result<Flow, RuntimeError> {
+        let scope = Environment::new(Some(self.environment.clone()));
+        self.execute_in_scope(statements, scope)
+    }
+
+    fn execute_function_body(&mut self, body: &[Stmt]) -> Result<Flow, RuntimeError> {
         for stmt in body {
-            let result = self.execute(stmt)?;
-            if result.is_some() {
-                return Ok(result);
+            let flow = self.execute(stmt)?;
+            if !matches!(flow, Flow::Normal) {
+                return Ok(flow);
             }
         }
-        Ok(None)
+        Ok(Flow::Normal)
     }
 
-    fn evaluate_strings(&mut self, expr: Expr) -> Result<String, String> {
+    fn evaluate_strings(&mut self, expr: &Expr) -> Result<String, RuntimeError> {
         match expr {
-            Expr::Literal(Token::StringLiteral(val)) => Ok(val),
+            Expr::Literal {
+                value: Token::StringLiteral(val),
+                ..
+            } => Ok(val.clone()),
             _ => Ok(self.evaluate(expr)?.to_string()),
         }
     }
@@ -0,0 +1,71 @@
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+// Entrada y salida de los programas; `read_line` devuelve `None` al final de la entrada
+pub trait Io {
+    fn read_line(&mut self) -> std::io::Result<Option<String>>;
+    fn write(&mut self, text: &str) -> std::io::Result<()>;
+    fn flush(&mut self) -> std::io::Result<()>;
+}
+
+pub struct StdIo;
+
+impl Io for StdIo {
+    fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+
+    fn write(&mut self, text: &str) -> std::io::Result<()> {
+        std::io::stdout().lock().write_all(text.as_bytes())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+}
+
+// Las copias comparten los búferes, así quien la crea puede leer la salida
+// después de entregarla al intérprete
+#[derive(Clone, Default)]
+pub struct MemoryIo {
+    input: Rc<RefCell<String>>,
+    output: Rc<RefCell<String>>,
+}
+
+impl MemoryIo {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: Rc::new(RefCell::new(input.to_string())),
+            output: Rc::default(),
+        }
+    }
+
+    pub fn output(&self) -> String {
+        self.output.borrow().clone()
+    }
+}
+
+impl Io for MemoryIo {
+    fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        let mut input = self.input.borrow_mut();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        let end = input.find('\n').map_or(input.len(), |i| i + 1);
+        Ok(Some(input.drain(..end).collect()))
+    }
+
+    fn write(&mut self, text: &str) -> std::io::Result<()> {
+        self.output.borrow_mut().push_str(text);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,20 +1,60 @@
 use std::collections::HashMap;
 
+use crate::core::messages::{Message, MessageId};
 use crate::core::token::Token;
-use crate::core::token::TokenData;
+use crate::core::token::{TokenData, Trivia, TriviaKind};
 use crate::persistence::models::SyntaxConfig;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LexicalError {
-    pub message: String,
+    pub message: Message,
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
 }
 
 impl LexicalError {
-    fn new(message: &str, line: usize, column: usize) -> Self {
+    pub fn new(message: impl Into<Message>, line: usize, column: usize) -> Self {
         Self {
-            message: format!("{} (at {}:{})", message, line, column),
+            message: message.into(),
+            line,
+            column,
+            end_column: column + 1,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.message.id {
+            MessageId::UnexpectedCharacter
+            | MessageId::ExpectedSecondAmpersand
+            | MessageId::ExpectedSecondPipe => "E101",
+            MessageId::UnterminatedBlockComment
+            | MessageId::UnterminatedCharacter
+            | MessageId::UnterminatedString => "E102",
+            _ => "E103",
+        }
+    }
+}
+
+// Mismas reglas que `read_identifier`
+pub fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(char::is_alphabetic) && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// Texto original a partir de tokens del modo sin pérdida
+pub fn source_text(source: &str, tokens: &[TokenData]) -> String {
+    let mut text = String::with_capacity(source.len());
+    for token in tokens {
+        for trivia in &token.leading {
+            text.push_str(&source[trivia.start..trivia.end]);
+        }
+        text.push_str(&source[token.start..token.end]);
+        for trivia in &token.trailing {
+            text.push_str(&source[trivia.start..trivia.end]);
         }
     }
+    text
 }
 
 #[derive(Debug)]
@@ -23,12 +63,28 @@ pub struct Lexer {
     pub position: usize,
     pub line: usize,
     pub column: usize,
+    pub offset: usize,
+    pub start_line: usize,
+    pub start_offset: usize,
+    pub start_position: usize,
     pub keywords_lookup: HashMap<String, Token>,
+    pub errors: Vec<LexicalError>,
+    pub lossless: bool,
 }
 
 impl Lexer {
     fn emit(&self, kind: Token, start_col: usize) -> TokenData {
-        TokenData::new(kind, self.line, start_col)
+        TokenData {
+            kind,
+            line: self.start_line,
+            col: start_col,
+            end_line: self.line,
+            end_col: self.column,
+            start: self.start_offset,
+            end: self.offset,
+            leading: Vec::new(),
+            trailing: Vec::new(),
+        }
     }
 
     pub fn new(input: &str, config: &SyntaxConfig) -> Self {
@@ -49,6 +105,8 @@ impl Lexer {
         lookup.insert(config.keywords.switch.clone(), Token::Switch);
         lookup.insert(config.keywords.case.clone(), Token::Case);
         lookup.insert(config.keywords.default.clone(), Token::Default);
+        lookup.insert(config.keywords.r#break.clone(), Token::Break);
+        lookup.insert(config.keywords.r#continue.clone(), Token::Continue);
         lookup.insert(config.keywords.function.clone(), Token::Function);
         lookup.insert(config.keywords.r#return.clone(), Token::Return);
 
@@ -64,13 +122,26 @@ impl Lexer {
             position: 0,
             line: 1,
             column: 1,
+            offset: 0,
+            start_line: 1,
+            start_offset: 0,
+            start_position: 0,
             keywords_lookup: lookup,
+            errors: Vec::new(),
+            lossless: false,
         }
     }
 
+    // Conserva espacios y comentarios como trivia de los tokens
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
     fn next_token(&mut self) -> Result<TokenData, LexicalError> {
-        self.skip_whitespace();
         let start_col = self.column;
+        self.start_line = self.line;
+        self.start_offset = self.offset;
+        self.start_position = self.position;
 
         if self.is_at_end() {
             return Ok(self.emit(Token::EOF, start_col));
@@ -126,11 +197,7 @@ impl Lexer {
                 if self.match_char('&') {
                     Ok(self.emit(Token::And, start_col))
                 } else {
-                    Err(LexicalError::new(
-                        "Expected '&' after '&'",
-                        self.line,
-                        start_col,
-                    ))
+                    Err(self.error(MessageId::ExpectedSecondAmpersand, start_col))
                 }
             }
             '|' => {
@@ -138,11 +205,7 @@ impl Lexer {
                 if self.match_char('|') {
                     Ok(self.emit(Token::Or, start_col))
                 } else {
-                    Err(LexicalError::new(
-                        "Expected '|' after '|'",
-                        self.line,
-                        start_col,
-                    ))
+                    Err(self.error(MessageId::ExpectedSecondPipe, start_col))
                 }
             }
 
@@ -183,62 +246,110 @@ impl Lexer {
             // Aritmetic Operators
             '+' => {
                 self.advance();
-                return Ok(self.emit(Token::Plus, start_col));
+                Ok(self.emit(Token::Plus, start_col))
             }
             '-' => {
                 self.advance();
-                return Ok(self.emit(Token::Minus, start_col));
+                Ok(self.emit(Token::Minus, start_col))
             }
             '*' => {
                 self.advance();
-                return Ok(self.emit(Token::Multiply, start_col));
+                Ok(self.emit(Token::Multiply, start_col))
             }
             '/' => {
                 self.advance();
-                if self.match_char('/') {
-                    while !self.is_at_end() && self.peek() != '\n' {
-                        self.advance();
-                    }
-                    if !self.is_at_end() {
-                        self.advance();
-                    }
-
-                    return self.next_token();
-                }
-
                 Ok(self.emit(Token::Divide, start_col))
             }
             '%' => {
                 self.advance();
-                return Ok(self.emit(Token::Modulo, start_col));
+                Ok(self.emit(Token::Modulo, start_col))
             }
             '^' => {
                 self.advance();
-                return Ok(self.emit(Token::Power, start_col));
+                Ok(self.emit(Token::Power, start_col))
             }
 
             ';' => {
                 self.advance();
-                return Ok(self.emit(Token::Semi, start_col));
+                Ok(self.emit(Token::Semi, start_col))
             }
-            '"' => return self.read_string(start_col),
-            '\'' => return self.read_char(start_col),
+            '"' => self.read_string(start_col),
+            '\'' => self.read_char(start_col),
 
             _ => {
-                let msg = format!("Unexpected character '{}' at position {}", c, self.position);
-                return Err(LexicalError::new(&msg, self.line, self.column));
+                self.advance();
+                let message = Message::new(MessageId::UnexpectedCharacter, vec![c.to_string()]);
+                Err(self.error(message, start_col))
             }
         }
     }
 
+    // El error cubre desde el inicio del token hasta la posición actual
+    fn error(&self, message: impl Into<Message>, start_col: usize) -> LexicalError {
+        let mut error = LexicalError::new(message, self.start_line, start_col);
+        if self.line == self.start_line {
+            error.end_column = self.column.max(start_col + 1);
+        }
+        error
+    }
+
     fn is_at_end(&self) -> bool {
         self.position >= self.input.len()
     }
 
-    fn skip_whitespace(&mut self) {
-        while !self.is_at_end() && self.input[self.position].is_whitespace() {
-            self.advance();
+    // Consume espacios y comentarios; la trivia final se detiene en el salto de
+    // línea. Solo se guarda en el modo sin pérdida
+    fn read_trivia(&mut self, trailing: bool) -> Vec<Trivia> {
+        let mut trivia = Vec::new();
+        while !self.is_at_end() {
+            let start = self.offset;
+            let next = self.input.get(self.position + 1).copied();
+            let kind = match (self.peek(), next) {
+                ('\n', _) if trailing => break,
+                (c, _) if c.is_whitespace() => {
+                    while !self.is_at_end()
+                        && self.peek().is_whitespace()
+                        && !(trailing && self.peek() == '\n')
+                    {
+                        self.advance();
+                    }
+                    TriviaKind::Whitespace
+                }
+                ('/', Some('/')) => {
+                    while !self.is_at_end() && self.peek() != '\n' {
+                        self.advance();
+                    }
+                    TriviaKind::LineComment
+                }
+                ('/', Some('*')) => {
+                    self.read_block_comment();
+                    TriviaKind::BlockComment
+                }
+                _ => break,
+            };
+            if self.lossless {
+                trivia.push(Trivia {
+                    kind,
+                    start,
+                    end: self.offset,
+                });
+            }
         }
+        trivia
+    }
+
+    fn read_block_comment(&mut self) {
+        let (line, column) = (self.line, self.column);
+        self.advance();
+        self.advance();
+        while !self.is_at_end() {
+            if self.advance() == '*' && self.match_char('/') {
+                return;
+            }
+        }
+        let mut error = LexicalError::new(MessageId::UnterminatedBlockComment, line, column);
+        error.end_column = column + 2;
+        self.errors.push(error);
     }
 
     fn match_char(&mut self, expected: char) -> bool {
@@ -272,17 +383,20 @@ impl Lexer {
     fn read_char(&mut self, start_col: usize) -> Result<TokenData, LexicalError> {
         self.advance();
         if self.is_at_end() {
-            let msg = format!("Unterminated character literal");
-            return Err(LexicalError::new(&msg, self.line, start_col));
+            return Err(self.error(MessageId::UnterminatedCharacter, start_col));
         }
         let content: char = self.advance();
 
         if self.peek() != '\'' {
-            let msg = format!("Invalid character literal");
-            return Err(LexicalError::new(&msg, self.line, start_col));
+            // Se descarta el resto del literal para no arrastrar más errores
+            while !self.is_at_end() && self.peek() != '\'' && self.peek() != '\n' {
+                self.advance();
+            }
+            self.match_char('\'');
+            return Err(self.error(MessageId::InvalidCharacter, start_col));
         }
         self.advance();
-        return Ok(self.emit(Token::CharLiteral(content), start_col));
+        Ok(self.emit(Token::CharLiteral(content), start_col))
     }
 
     fn read_string(&mut self, start_col: usize) -> Result<TokenData, LexicalError> {
@@ -292,12 +406,11 @@ impl Lexer {
             self.advance();
         }
         if self.is_at_end() {
-            let msg = format!("Unterminated string literal");
-            return Err(LexicalError::new(&msg, self.line, start_col));
+            return Err(self.error(MessageId::UnterminatedString, start_col));
         }
         let content: String = self.input[start..self.position].iter().collect();
         self.advance();
-        return Ok(self.emit(Token::StringLiteral(content), start_col));
+        Ok(self.emit(Token::StringLiteral(content), start_col))
     }
 
     fn read_number(&mut self, start_col: usize) -> Result<TokenData, LexicalError> {
@@ -310,8 +423,7 @@ impl Lexer {
             is_float = true;
             self.advance();
             if !self.peek().is_ascii_digit() {
-                let msg = format!("Expected digit after decimal point");
-                return Err(LexicalError::new(&msg, self.line, start_col));
+                return Err(self.error(MessageId::ExpectedDecimalDigit, start_col));
             }
             while !self.is_at_end() && self.input[self.position].is_ascii_digit() {
                 self.advance();
@@ -320,30 +432,21 @@ impl Lexer {
         let literal: String = self.input[start..self.position].iter().collect();
 
         if !self.is_at_end() && self.input[self.position].is_alphabetic() {
-            return Err(LexicalError::new(
-                format!("Invalid number literal at position {}", self.position).as_str(),
-                self.line,
-                start_col,
-            ));
+            while !self.is_at_end() && (self.peek().is_alphanumeric() || self.peek() == '_') {
+                self.advance();
+            }
+            return Err(self.error(MessageId::InvalidNumber, start_col));
         }
 
         if is_float {
             match literal.parse::<f64>() {
                 Ok(n) => Ok(self.emit(Token::FloatLiteral(n), start_col)),
-                Err(_) => Err(LexicalError::new(
-                    "Invalid float literal",
-                    self.line,
-                    start_col,
-                )),
+                Err(_) => Err(self.error(MessageId::InvalidFloat, start_col)),
             }
         } else {
             match literal.parse::<i64>() {
                 Ok(n) => Ok(self.emit(Token::IntegerLiteral(n), start_col)),
-                Err(_) => Err(LexicalError::new(
-                    "Invalid integer literal",
-                    self.line,
-                    start_col,
-                )),
+                Err(_) => Err(self.error(MessageId::InvalidInteger, start_col)),
             }
         }
     }
@@ -356,17 +459,10 @@ impl Lexer {
         }
     }
 
-    fn peek_next(&self) -> char {
-        if self.position + 1 >= self.input.len() {
-            '\0'
-        } else {
-            self.input[self.position + 1]
-        }
-    }
-
     fn advance(&mut self) -> char {
         let c = self.input[self.position];
         self.position += 1;
+        self.offset += c.len_utf8();
 
         if c == '\n' {
             self.line += 1;
@@ -377,11 +473,25 @@ impl Lexer {
         c
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<TokenData>, LexicalError> {
+    // Cada error se convierte en un token `Error` y el análisis continúa
+    pub fn tokenize(&mut self) -> (Vec<TokenData>, Vec<LexicalError>) {
         let mut tokens: Vec<TokenData> = Vec::new();
 
         loop {
-            let token = self.next_token()?;
+            let leading = self.read_trivia(false);
+            let mut token = match self.next_token() {
+                Ok(token) => token,
+                Err(error) => {
+                    let start_col = error.column;
+                    let text: String = self.input[self.start_position..self.position]
+                        .iter()
+                        .collect();
+                    self.errors.push(error);
+                    self.emit(Token::Error(text), start_col)
+                }
+            };
+            token.leading = leading;
+            token.trailing = self.read_trivia(true);
             let is_eof = token.kind == Token::EOF;
             tokens.push(token);
 
@@ -390,6 +500,6 @@ impl Lexer {
             }
         }
 
-        Ok(tokens)
+        (tokens, std::mem::take(&mut self.errors))
     }
 }
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::core::token::Token;
+use crate::persistence::loader;
+use crate::persistence::models::SyntaxConfig;
+
+// Cada mensaje tiene un identificador estable, la sección del catálogo donde
+// se traduce y el texto que se muestra sin catálogo, con `{}` en el lugar de
+// los valores
+macro_rules! messages {
+    ($([$section:literal] $($id:ident = $text:literal,)*)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum MessageId {
+            $($($id,)*)*
+        }
+
+        impl MessageId {
+            pub const ALL: &[MessageId] = &[$($(MessageId::$id,)*)*];
+
+            pub fn section(self) -> &'static str {
+                match self {
+                    $($(MessageId::$id => $section,)*)*
+                }
+            }
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($(MessageId::$id => stringify!($id),)*)*
+                }
+            }
+
+            pub fn text(self) -> &'static str {
+                match self {
+                    $($(MessageId::$id => $text,)*)*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    ["lexer"]
+    ExpectedSecondAmpersand = "Expected '&' after '&'",
+    ExpectedSecondPipe = "Expected '|' after '|'",
+    UnexpectedCharacter = "Unexpected character '{}'",
+    UnterminatedBlockComment = "Unterminated block comment",
+    UnterminatedCharacter = "Unterminated character literal",
+    InvalidCharacter = "Invalid character literal",
+    UnterminatedString = "Unterminated string literal",
+    ExpectedDecimalDigit = "Expected digit after decimal point",
+    InvalidNumber = "Invalid number literal",
+    InvalidFloat = "Invalid float literal",
+    InvalidInteger = "Invalid integer literal",
+
+    ["parser"]
+    UnexpectedEndOfInput = "Unexpected end of input",
+    ExpectedEndOfExpression = "Se esperaba el final de la expresión",
+    UnrecognizedStatement = "Sentencia no reconocida: {}",
+    ExpectedExpression = "Se esperaba una expresión",
+    ExpectedType = "Se esperaba un tipo",
+    ExpectedVariableName = "Se esperaba un nombre de variable",
+    InvalidAssignTarget = "Solo se puede asignar valores a variables",
+    ExpectedReturnType = "Se esperaba tipo de retorno",
+    ExpectedFunctionKeyword = "Se esperaba la palabra reservada '{}'",
+    ExpectedFunctionName = "Se esperaba nombre de función",
+    ExpectedFunctionNameFound = "Se esperaba nombre de función, se obtuvo {}",
+    ExpectedParamType = "Se esperaba tipo de parámetro",
+    ExpectedParamName = "Se esperaba nombre de parámetro",
+    ExpectedLeftParen = "Se esperaba '('",
+    ExpectedRightParen = "Se esperaba ')'",
+    ExpectedLeftBrace = "Se esperaba '{'",
+    ExpectedRightBrace = "Se esperaba '}'",
+    ExpectedColon = "Se esperaba ':'",
+    ExpectedBlockStart = "Se esperaba '{' para iniciar el bloque",
+    ExpectedBlockEnd = "Se esperaba '}' para cerrar el bloque",
+    ExpectedParenAfterKeyword = "Se esperaba '(' después de '{}'",
+    ExpectedParenAfterCondition = "Se esperaba ')' después de la condición",
+    ExpectedParenAfterExpression = "Se esperaba ')' después de la expresión",
+    ExpectedParenAfterForIncrement = "Se esperaba ')' después del incremento del {}",
+    ExpectedSemiAfterDeclaration = "Se esperaba ';' al final de la declaración",
+    ExpectedSemiAfterExpression = "Se esperaba ';' después de la expresión",
+    ExpectedSemiAfterAssignment = "Se esperaba ';' después de la asignación",
+    ExpectedSemiAfterReturn = "Se esperaba ';' después del valor de retorno",
+    ExpectedSemiAfterKeyword = "Se esperaba ';' después de '{}'",
+    ExpectedSemiAfterForCondition = "Se esperaba ';' después de la condición del {}",
+    ExpectedCase = "Se esperaba '{}' o '{}' dentro del {}",
+    BreakOutsideLoop = "'{}' solo puede usarse dentro de un bucle o un {}",
+    ContinueOutsideLoop = "'{}' solo puede usarse dentro de un bucle",
+    HelpMissingSemicolon = "agregue ';' al final de la sentencia anterior",
+
+    // Compartidos por el verificador de tipos y la ejecución
+    ["common"]
+    UndefinedVariable = "Variable no definida: {}",
+    UndefinedFunction = "Función no definida: {}",
+    MissingInitializer = "La variable '{}' necesita un valor inicial",
+    TypeMismatch = "Error de tipo: se esperaba {} pero se recibió {}",
+    ArityMismatch = "La función '{}' espera {} argumentos pero recibió {}",
+    IncompatibleOperands = "Operación no soportada o tipos incompatibles: {} {} {}",
+    MissingReturn = "La función '{}' puede terminar sin retornar un valor de tipo {}",
+    HelpDeclareVariable = "declare '{}' con su tipo antes de usarla",
+
+    ["types"]
+    AssignToUndefined = "Variable '{}' no definida",
+    InvalidVariableType = "La variable '{}' no puede ser de tipo {}",
+    InvalidOperandType = "Operador {} no aplicable a {}",
+    LogicalOperandNotBool = "El operador '{}' requiere operandos {}, se obtuvo {}",
+    ConditionNotBool = "La condición debe ser {}, se obtuvo {}",
+    IncomparableCase = "El caso de tipo {} no se puede comparar con {}",
+    ReturnOutsideFunction = "'{}' fuera de una función",
+    ExpectedReturnValue = "Se esperaba un valor de retorno de tipo {}",
+    VoidReturnsValue = "Una función {} no puede retornar un valor",
+
+    ["compiler"]
+    BreakWithoutTarget = "'{}' fuera de un bucle o {}",
+    ContinueWithoutLoop = "'{}' fuera de un bucle",
+    UnknownUnaryOperator = "Operador unario desconocido {}",
+    UnsupportedBinaryOperator = "Operador binario no soportado {}",
+    UnsupportedLogicalOperator = "Operador lógico no soportado {}",
+    TooManyConstants = "Demasiadas constantes",
+    TooManyFunctions = "Demasiadas funciones",
+    TooManyLocals = "Demasiadas variables locales",
+    TooManyArguments = "Demasiados argumentos",
+    TooManyIdentifiers = "Demasiados identificadores",
+    TooManyTypes = "Demasiados tipos",
+
+    ["runtime"]
+    DivisionByZero = "División por cero",
+    Overflow = "Desbordamiento aritmético",
+    InvalidOperand = "Operador '{}' no aplicable a {}",
+    StackOverflow = "Desbordamiento de pila: se superó el límite de {} llamadas anidadas",
+    NativeStackExhausted = "Desbordamiento de pila: se agotó la pila nativa con {} llamadas anidadas",
+    StackUnavailable = "No se pudo reservar una pila de {} MiB para la ejecución",
+    StepLimit = "Límite de ejecución alcanzado: se superaron los {} pasos de ejecución",
+    TimeLimit = "Límite de ejecución alcanzado: se superó el tiempo máximo de {}",
+    StringLimit = "Límite de ejecución alcanzado: una cadena superó los {} bytes",
+    ValueLimit = "Límite de ejecución alcanzado: se superaron las {} variables en memoria",
+    Cancelled = "Ejecución cancelada",
+    EndOfInput = "Se esperaba una entrada pero se llegó al final",
+    IoError = "Error de entrada/salida: {}",
+    NativeError = "{}",
+    CalledFrom = "en la función '{}', llamada en {}:{}",
+    MoreCalls = "... y {} llamadas más",
+
+    ["translator"]
+    ReservedIdentifier = "El identificador '{}' es una palabra reservada ({}) en el dialecto de destino",
+}
+
+impl MessageId {
+    pub fn arity(self) -> usize {
+        placeholders(self.text())
+    }
+}
+
+fn placeholders(template: &str) -> usize {
+    template.matches("{}").count()
+}
+
+// Valor que se copia a un `{}`. Los tipos y palabras reservadas se guardan
+// como token y se muestran con la palabra del dialecto activo
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg {
+    Text(String),
+    Word(Token),
+}
+
+impl From<String> for Arg {
+    fn from(text: String) -> Self {
+        Arg::Text(text)
+    }
+}
+
+impl From<&str> for Arg {
+    fn from(text: &str) -> Self {
+        Arg::Text(text.to_string())
+    }
+}
+
+impl From<Token> for Arg {
+    fn from(token: Token) -> Self {
+        Arg::Word(token)
+    }
+}
+
+impl Arg {
+    fn render(&self, syntax: &SyntaxConfig) -> String {
+        let token = match self {
+            Arg::Text(text) => return text.clone(),
+            Arg::Word(token) => token,
+        };
+        if let Some(word) = syntax.word(token).or(token.symbol()) {
+            return word.to_string();
+        }
+        match token {
+            Token::Identifier(text) | Token::Error(text) => text.clone(),
+            Token::IntegerLiteral(n) => n.to_string(),
+            Token::FloatLiteral(n) => n.to_string(),
+            Token::StringLiteral(text) => format!("\"{}\"", text),
+            Token::CharLiteral(c) => format!("'{}'", c),
+            _ => "EOF".to_string(),
+        }
+    }
+}
+
+// Mensaje emitido en el sitio del error; el texto se arma al mostrarlo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub id: MessageId,
+    pub args: Vec<Arg>,
+}
+
+impl Message {
+    pub fn new<A: Into<Arg>>(id: MessageId, args: Vec<A>) -> Self {
+        Self {
+            id,
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<MessageId> for Message {
+    fn from(id: MessageId) -> Self {
+        Self::new(id, Vec::<Arg>::new())
+    }
+}
+
+// Sin sintaxis activa las palabras son las de la sintaxis incluida
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&fill(self.id.text(), &self.args, loader::default_syntax()))
+    }
+}
+
+fn fill(template: &str, args: &[Arg], syntax: &SyntaxConfig) -> String {
+    let mut text = String::new();
+    let mut args = args.iter();
+    let mut parts = template.split("{}").peekable();
+    while let Some(part) = parts.next() {
+        text.push_str(part);
+        if parts.peek().is_some()
+            && let Some(arg) = args.next()
+        {
+            text.push_str(&arg.render(syntax));
+        }
+    }
+    text
+}
+
+// Catálogo de traducciones por sección ("lexer", "parser", "common", "types",
+// "compiler", "runtime", "translator"). Cada clave es el nombre de un
+// `MessageId` y los valores se copian a los `{}` de la traducción en orden
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "HashMap<String, HashMap<String, String>>")]
+pub struct MessageCatalog {
+    messages: HashMap<MessageId, String>,
+}
+
+impl TryFrom<HashMap<String, HashMap<String, String>>> for MessageCatalog {
+    type Error = String;
+
+    fn try_from(sections: HashMap<String, HashMap<String, String>>) -> Result<Self, String> {
+        let mut messages = HashMap::new();
+        for (section, entries) in sections {
+            for (name, translation) in entries {
+                let id = MessageId::ALL
+                    .iter()
+                    .find(|id| id.section() == section && id.name() == name)
+                    .ok_or_else(|| format!("Mensaje desconocido: {}.{}", section, name))?;
+                if placeholders(&translation) != id.arity() {
+                    return Err(format!(
+                        "El mensaje {}.{} debe tener {} valores {{}}",
+                        section,
+                        name,
+                        id.arity()
+                    ));
+                }
+                messages.insert(*id, translation);
+            }
+        }
+        Ok(Self { messages })
+    }
+}
+
+impl MessageCatalog {
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn get(&self, id: MessageId) -> Option<&str> {
+        self.messages.get(&id).map(String::as_str)
+    }
+
+    // Sin traducción se usa el texto original; las palabras salen de `syntax`
+    pub fn translate(&self, message: &Message, syntax: &SyntaxConfig) -> String {
+        let template = self.get(message.id).unwrap_or(message.id.text());
+        fill(template, &message.args, syntax)
+    }
+}
@@ -1,19 +1,64 @@
-use crate::core::ast::{Expr, Stmt};
+use crate::core::ast::{Expr, Span, Stmt};
+use crate::core::messages::{Message, MessageId};
 use crate::core::token::{Token, TokenData};
 
 #[derive(Debug, Clone)]
 pub struct ParseError {
-    pub message: String,
+    pub message: Message,
     pub line: usize,
     pub column: usize,
+    pub end_column: usize,
 }
 
 impl ParseError {
-    pub fn new(message: &str, line: usize, column: usize) -> Self {
+    pub fn new(message: impl Into<Message>, line: usize, column: usize) -> Self {
         Self {
-            message: message.to_string(),
+            message: message.into(),
             line,
             column,
+            end_column: column + 1,
+        }
+    }
+
+    pub fn at(message: impl Into<Message>, token: &TokenData) -> Self {
+        let mut error = Self::new(message, token.line, token.col);
+        if token.end_line == token.line {
+            error.end_column = token.end_col.max(token.col + 1);
+        }
+        error
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.message.id {
+            MessageId::ExpectedSemiAfterDeclaration
+            | MessageId::ExpectedSemiAfterExpression
+            | MessageId::ExpectedSemiAfterAssignment
+            | MessageId::ExpectedSemiAfterReturn
+            | MessageId::ExpectedSemiAfterKeyword
+            | MessageId::ExpectedSemiAfterForCondition => "E201",
+            MessageId::ExpectedLeftParen
+            | MessageId::ExpectedRightParen
+            | MessageId::ExpectedLeftBrace
+            | MessageId::ExpectedRightBrace
+            | MessageId::ExpectedColon
+            | MessageId::ExpectedBlockStart
+            | MessageId::ExpectedBlockEnd
+            | MessageId::ExpectedParenAfterKeyword
+            | MessageId::ExpectedParenAfterCondition
+            | MessageId::ExpectedParenAfterExpression
+            | MessageId::ExpectedParenAfterForIncrement => "E202",
+            MessageId::ExpectedExpression | MessageId::ExpectedEndOfExpression => "E203",
+            MessageId::ExpectedType
+            | MessageId::ExpectedVariableName
+            | MessageId::ExpectedReturnType
+            | MessageId::ExpectedFunctionKeyword
+            | MessageId::ExpectedFunctionName
+            | MessageId::ExpectedFunctionNameFound
+            | MessageId::ExpectedParamType
+            | MessageId::ExpectedParamName => "E204",
+            MessageId::BreakOutsideLoop | MessageId::ContinueOutsideLoop => "E205",
+            MessageId::UnexpectedEndOfInput => "E206",
+            _ => "E207",
         }
     }
 }
@@ -21,25 +66,112 @@ impl ParseError {
 pub struct Parser {
     tokens: Vec<TokenData>,
     current: usize,
+    errors: Vec<ParseError>,
+    loop_depth: usize,
+    switch_depth: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<TokenData>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+            loop_depth: 0,
+            switch_depth: 0,
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, ParseError> {
+    // Devuelve el AST parcial junto con todos los errores encontrados
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<ParseError>) {
         let mut statements = Vec::new();
         while !self.is_at_end() {
-            statements.push(self.parse_statement()?);
+            if let Some(stmt) = self.parse_declaration() {
+                statements.push(stmt);
+            }
+        }
+        (statements, std::mem::take(&mut self.errors))
+    }
+
+    // Una expresión que ocupa toda la entrada, con `;` opcional, como las del REPL
+    pub fn parse_standalone_expression(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.parse_expression()?;
+        if self.check(Token::Semi) {
+            self.advance();
+        }
+        if !self.is_at_end() {
+            return Err(self.error_at_current(MessageId::ExpectedEndOfExpression));
+        }
+        Ok(expr)
+    }
+
+    // Registra el error y descarta tokens hasta un punto seguro para seguir
+    fn parse_declaration(&mut self) -> Option<Stmt> {
+        let start = self.current;
+        match self.parse_statement() {
+            Ok(stmt) => Some(stmt),
+            Err(error) => {
+                self.errors.push(error);
+                self.synchronize();
+                if self.current == start {
+                    self.advance();
+                }
+                None
+            }
+        }
+    }
+
+    // Los bloques abiertos durante la sincronización se descartan completos
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token.kind {
+                Token::EOF => return,
+                Token::LeftBrace => depth += 1,
+                Token::RightBrace if depth == 0 => return,
+                Token::RightBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                }
+                Token::Semi if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                Token::If
+                | Token::While
+                | Token::For
+                | Token::Break
+                | Token::Continue
+                | Token::Switch
+                | Token::Function
+                | Token::Return
+                | Token::Print
+                | Token::Println
+                | Token::IntType
+                | Token::FloatType
+                | Token::StringType
+                | Token::BoolType
+                | Token::CharType
+                | Token::BigIntType
+                | Token::DoubleType
+                | Token::VoidType
+                    if depth == 0 =>
+                {
+                    return;
+                }
+                _ => {}
+            }
+            self.advance();
         }
-        Ok(statements)
     }
 
     fn parse_statement(&mut self) -> Result<Stmt, ParseError> {
         let t = self
             .peek()
-            .ok_or(ParseError::new("Unexpected end of input", 0, 0))?;
+            .ok_or_else(|| self.error_at_current(MessageId::UnexpectedEndOfInput))?;
 
         match &t.kind {
             Token::IntType
@@ -53,7 +185,7 @@ impl Parser {
                 if self
                     .tokens
                     .get(self.current + 1)
-                    .map_or(false, |t| t.kind == Token::Function)
+                    .is_some_and(|t| t.kind == Token::Function)
                 {
                     self.parse_function_declaration()
                 } else {
@@ -61,40 +193,63 @@ impl Parser {
                 }
             }
             Token::If => self.parse_if_statement(),
-            Token::LeftBrace => Ok(Stmt::Block(self.parse_block()?)),
-            Token::Identifier(_) => self.parse_assignment_or_expression(),
+            Token::LeftBrace => {
+                let span = self.current_span();
+                Ok(Stmt::Block {
+                    statements: self.parse_block()?,
+                    span: self.span_from(span),
+                })
+            }
+            Token::Identifier(_) => self.parse_assignment_or_expression(true),
             Token::Return => self.parse_return_statement(),
             Token::While => self.parse_while_statement(),
+            Token::For => self.parse_for_statement(),
+            Token::Break => self.parse_break_statement(),
+            Token::Continue => self.parse_continue_statement(),
             Token::Print => self.parse_print_statement(),
             Token::Println => self.parse_println_statement(),
             Token::Switch => self.parse_switch_statement(),
-            _ => Err(ParseError::new(
-                &format!("Sentencia no reconocida: {:?}", t.kind),
-                t.line,
-                t.col,
+            _ => Err(ParseError::at(
+                Message::new(MessageId::UnrecognizedStatement, vec![t.kind.clone()]),
+                t,
             )),
         }
     }
 
     fn parse_print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         self.advance();
-        self.consume(Token::LeftParen, "Se esperaba '(' después de 'if'")?;
+        self.consume(
+            Token::LeftParen,
+            Message::new(MessageId::ExpectedParenAfterKeyword, vec![Token::Print]),
+        )?;
         let value = self.parse_expression()?;
-        self.consume(Token::RightParen, "Se esperaba ')' después de la condición")?;
-        self.consume(Token::Semi, "Se esperaba ';' después de la expresión")?;
-        Ok(Stmt::Print { value })
+        self.consume(Token::RightParen, MessageId::ExpectedParenAfterCondition)?;
+        self.consume(Token::Semi, MessageId::ExpectedSemiAfterExpression)?;
+        Ok(Stmt::Print {
+            value,
+            span: self.span_from(span),
+        })
     }
 
     fn parse_println_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         self.advance();
-        self.consume(Token::LeftParen, "Se esperaba '(' después de 'if'")?;
+        self.consume(
+            Token::LeftParen,
+            Message::new(MessageId::ExpectedParenAfterKeyword, vec![Token::Println]),
+        )?;
         let value = self.parse_expression()?;
-        self.consume(Token::RightParen, "Se esperaba ')' después de la condición")?;
-        self.consume(Token::Semi, "Se esperaba ';' después de la expresión")?;
-        Ok(Stmt::Println { value })
+        self.consume(Token::RightParen, MessageId::ExpectedParenAfterCondition)?;
+        self.consume(Token::Semi, MessageId::ExpectedSemiAfterExpression)?;
+        Ok(Stmt::Println {
+            value,
+            span: self.span_from(span),
+        })
     }
 
     fn parse_return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         self.advance();
 
         let mut value = None;
@@ -102,19 +257,63 @@ impl Parser {
             value = Some(self.parse_expression()?);
         }
 
-        self.consume(Token::Semi, "Se esperaba ';' después del valor de retorno")?;
-        Ok(Stmt::Return(value))
+        self.consume(Token::Semi, MessageId::ExpectedSemiAfterReturn)?;
+        Ok(Stmt::Return {
+            value,
+            span: self.span_from(span),
+        })
+    }
+
+    fn parse_break_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
+        let allowed = self.loop_depth > 0 || self.switch_depth > 0;
+        let keyword = self.advance().unwrap();
+        if !allowed {
+            return Err(ParseError::at(
+                Message::new(
+                    MessageId::BreakOutsideLoop,
+                    vec![Token::Break, Token::Switch],
+                ),
+                keyword,
+            ));
+        }
+        self.consume(
+            Token::Semi,
+            Message::new(MessageId::ExpectedSemiAfterKeyword, vec![Token::Break]),
+        )?;
+        Ok(Stmt::Break {
+            span: self.span_from(span),
+        })
+    }
+
+    fn parse_continue_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
+        let allowed = self.loop_depth > 0;
+        let keyword = self.advance().unwrap();
+        if !allowed {
+            return Err(ParseError::at(
+                Message::new(MessageId::ContinueOutsideLoop, vec![Token::Continue]),
+                keyword,
+            ));
+        }
+        self.consume(
+            Token::Semi,
+            Message::new(MessageId::ExpectedSemiAfterKeyword, vec![Token::Continue]),
+        )?;
+        Ok(Stmt::Continue {
+            span: self.span_from(span),
+        })
     }
 
     fn parse_function_declaration(&mut self) -> Result<Stmt, ParseError> {
-        let return_type = self
-            .advance()
-            .expect("Se esperaba tipo de retorno")
-            .kind
-            .clone();
+        let span = self.current_span();
+        let return_type = match self.advance() {
+            Some(t) => t.kind.clone(),
+            None => return Err(self.error_at_current(MessageId::ExpectedReturnType)),
+        };
         self.consume(
             Token::Function,
-            "Se esperaba la palabra reservada 'function'",
+            Message::new(MessageId::ExpectedFunctionKeyword, vec![Token::Function]),
         )?;
         let name = match self.peek().map(|t| t.kind.clone()) {
             Some(Token::Identifier(n)) => {
@@ -122,40 +321,33 @@ impl Parser {
                 n
             }
             Some(t) => {
-                return Err(ParseError::new(
-                    &format!("Se esperaba nombre de función, se obtuvo {:?}", t),
-                    0,
-                    0,
-                ));
+                return Err(self.error_at_current(Message::new(
+                    MessageId::ExpectedFunctionNameFound,
+                    vec![t],
+                )));
             }
-            None => return Err(ParseError::new("Se esperaba nombre de función", 0, 0)),
+            None => return Err(self.error_at_current(MessageId::ExpectedFunctionName)),
         };
 
-        self.consume(Token::LeftParen, "Se esperaba '('")?;
+        self.consume(Token::LeftParen, MessageId::ExpectedLeftParen)?;
         let mut params = Vec::new();
         if !self.check(Token::RightParen) {
             loop {
-                let p_type = self
-                    .advance()
-                    .expect("Se esperaba tipo de parámetro")
-                    .kind
-                    .clone();
+                let p_type = match self.advance() {
+                    Some(t) => t.kind.clone(),
+                    None => return Err(self.error_at_current(MessageId::ExpectedParamType)),
+                };
                 match self.advance() {
                     Some(TokenData {
                         kind: Token::Identifier(p_name),
-                        line: _,
-                        col: _,
+                        ..
                     }) => {
                         params.push((p_type, p_name.clone()));
                     }
                     Some(t) => {
-                        return Err(ParseError::new(
-                            "Se esperaba nombre de parámetro",
-                            t.line,
-                            t.col,
-                        ));
+                        return Err(ParseError::at(MessageId::ExpectedParamName, t));
                     }
-                    None => return Err(ParseError::new("Se esperaba nombre de parámetro", 0, 0)),
+                    None => return Err(self.error_at_current(MessageId::ExpectedParamName)),
                 }
 
                 if !self.check(Token::Comma) {
@@ -164,57 +356,102 @@ impl Parser {
                 self.advance();
             }
         }
-        self.consume(Token::RightParen, "Se esperaba ')'")?;
-        let body = self.parse_block()?;
+        self.consume(Token::RightParen, MessageId::ExpectedRightParen)?;
+        // break y continue no atraviesan los límites de una función
+        let enclosing = (
+            std::mem::take(&mut self.loop_depth),
+            std::mem::take(&mut self.switch_depth),
+        );
+        let body = self.parse_block();
+        (self.loop_depth, self.switch_depth) = enclosing;
+        let body = body?;
         Ok(Stmt::Function {
             return_type,
             name,
             params,
             body,
+            span: self.span_from(span),
         })
     }
 
     fn parse_block(&mut self) -> Result<Vec<Stmt>, ParseError> {
-        self.consume(Token::LeftBrace, "Se esperaba '{' para iniciar el bloque")?;
+        self.consume(Token::LeftBrace, MessageId::ExpectedBlockStart)?;
 
         let mut statements = Vec::new();
 
         while !self.check(Token::RightBrace) && !self.is_at_end() {
-            statements.push(self.parse_statement()?);
+            if let Some(stmt) = self.parse_declaration() {
+                statements.push(stmt);
+            }
         }
 
-        self.consume(Token::RightBrace, "Se esperaba '}' para cerrar el bloque")?;
+        self.consume(Token::RightBrace, MessageId::ExpectedBlockEnd)?;
         Ok(statements)
     }
 
-    fn parse_assignment_or_expression(&mut self) -> Result<Stmt, ParseError> {
+    fn parse_loop_body(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.loop_depth += 1;
+        let body = self.parse_block();
+        self.loop_depth -= 1;
+        body
+    }
+
+    fn parse_case_body(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.switch_depth += 1;
+        let body = self.parse_block();
+        self.switch_depth -= 1;
+        body
+    }
+
+    // El incremento de un `for` es una asignación sin ';' final
+    fn parse_assignment_or_expression(&mut self, terminated: bool) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         let expr = self.parse_expression()?;
 
         if self.check(Token::Assign) {
             self.advance();
             let value = self.parse_expression()?;
-            self.consume(Token::Semi, "Se esperaba ';' después de la asignación")?;
+            if terminated {
+                self.consume(Token::Semi, MessageId::ExpectedSemiAfterAssignment)?;
+            }
 
-            if let Expr::Variable(name) = expr {
-                return Ok(Stmt::Assignment { name, value });
+            if let Expr::Variable { name, .. } = expr {
+                return Ok(Stmt::Assignment {
+                    name,
+                    value,
+                    span: self.span_from(span),
+                });
             } else {
-                return Err(ParseError::new(
-                    "Solo se puede asignar valores a variables",
-                    0,
-                    0,
-                ));
+                let mut error = ParseError::new(
+                    MessageId::InvalidAssignTarget,
+                    span.start.line,
+                    span.start.column,
+                );
+                if span.end.line == span.start.line {
+                    error.end_column = span.end.column;
+                }
+                return Err(error);
             }
         }
 
-        self.consume(Token::Semi, "Se esperaba ';' después de la expresión")?;
-        Ok(Stmt::Expression(expr))
+        if terminated {
+            self.consume(Token::Semi, MessageId::ExpectedSemiAfterExpression)?;
+        }
+        Ok(Stmt::Expression {
+            expr,
+            span: self.span_from(span),
+        })
     }
 
     fn parse_if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         self.advance();
-        self.consume(Token::LeftParen, "Se esperaba '(' después de 'if'")?;
+        self.consume(
+            Token::LeftParen,
+            Message::new(MessageId::ExpectedParenAfterKeyword, vec![Token::If]),
+        )?;
         let condition = self.parse_expression()?;
-        self.consume(Token::RightParen, "Se esperaba ')' después de la condición")?;
+        self.consume(Token::RightParen, MessageId::ExpectedParenAfterCondition)?;
 
         let then_branch = self.parse_block()?;
 
@@ -232,39 +469,96 @@ impl Parser {
             condition,
             then_branch,
             else_branch,
+            span: self.span_from(span),
         })
     }
 
     fn parse_while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         self.advance();
-        self.consume(Token::LeftParen, "Se esperaba '(' después de 'while'")?;
+        self.consume(
+            Token::LeftParen,
+            Message::new(MessageId::ExpectedParenAfterKeyword, vec![Token::While]),
+        )?;
         let condition = self.parse_expression()?;
-        self.consume(Token::RightParen, "Se esperaba ')' después de la condición")?;
+        self.consume(Token::RightParen, MessageId::ExpectedParenAfterCondition)?;
+
+        let body = self.parse_loop_body()?;
+
+        Ok(Stmt::While {
+            condition,
+            body,
+            span: self.span_from(span),
+        })
+    }
+
+    fn parse_for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
+        self.advance();
+        self.consume(
+            Token::LeftParen,
+            Message::new(MessageId::ExpectedParenAfterKeyword, vec![Token::For]),
+        )?;
+
+        let initializer = match self.peek().map(|t| &t.kind) {
+            Some(Token::Semi) => {
+                self.advance();
+                None
+            }
+            Some(
+                Token::IntType
+                | Token::FloatType
+                | Token::StringType
+                | Token::BoolType
+                | Token::CharType
+                | Token::BigIntType
+                | Token::DoubleType,
+            ) => Some(Box::new(self.parse_var_declaration()?)),
+            _ => Some(Box::new(self.parse_assignment_or_expression(true)?)),
+        };
+
+        let mut condition = None;
+        if !self.check(Token::Semi) {
+            condition = Some(self.parse_expression()?);
+        }
+        self.consume(
+            Token::Semi,
+            Message::new(MessageId::ExpectedSemiAfterForCondition, vec![Token::For]),
+        )?;
+
+        let mut increment = None;
+        if !self.check(Token::RightParen) {
+            increment = Some(Box::new(self.parse_assignment_or_expression(false)?));
+        }
+        self.consume(
+            Token::RightParen,
+            Message::new(MessageId::ExpectedParenAfterForIncrement, vec![Token::For]),
+        )?;
 
-        let body = self.parse_block()?;
+        let body = self.parse_loop_body()?;
 
-        Ok(Stmt::While { condition, body })
+        Ok(Stmt::For {
+            initializer,
+            condition,
+            increment,
+            body,
+            span: self.span_from(span),
+        })
     }
 
     fn parse_var_declaration(&mut self) -> Result<Stmt, ParseError> {
-        let ty = self
-            .advance()
-            .ok_or(ParseError::new("Se esperaba un tipo", 0, 0))?
-            .kind
-            .clone();
-
-        let name = if let Token::Identifier(ref n) = self.peek().unwrap().kind {
-            let name_string = n.clone();
-            self.advance();
-            name_string
-        } else {
-            let t = self.peek().unwrap();
-            return Err(ParseError::new(
-                "Se esperaba un nombre de variable",
-                t.line,
-                t.col,
-            ));
+        let span = self.current_span();
+        let ty = match self.advance() {
+            Some(t) => t.kind.clone(),
+            None => return Err(self.error_at_current(MessageId::ExpectedType)),
+        };
+
+        let name = match self.peek().map(|t| &t.kind) {
+            Some(Token::Identifier(name)) => name.clone(),
+            Some(_) => return Err(self.error_at_current(MessageId::ExpectedVariableName)),
+            None => return Err(self.error_at_current(MessageId::UnexpectedEndOfInput)),
         };
+        self.advance();
 
         let mut initializer = None;
         if self.check(Token::Assign) {
@@ -272,17 +566,53 @@ impl Parser {
             initializer = Some(self.parse_expression()?);
         }
 
-        self.consume(Token::Semi, "Se esperaba ';' al final de la declaración")?;
+        self.consume(Token::Semi, MessageId::ExpectedSemiAfterDeclaration)?;
         Ok(Stmt::VarDeclaration {
             ty,
             name,
             initializer,
+            span: self.span_from(span),
         })
     }
 
     fn parse_expression(&mut self) -> Result<Expr, ParseError> {
-        self.comparison()
+        self.logical_or()
+    }
+
+    fn logical_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.logical_and()?;
+
+        while self.check(Token::Or) {
+            let operator = self.advance().unwrap().kind.clone();
+            let right = self.logical_and()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+                span,
+            };
+        }
+        Ok(expr)
     }
+
+    fn logical_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.comparison()?;
+
+        while self.check(Token::And) {
+            let operator = self.advance().unwrap().kind.clone();
+            let right = self.comparison()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+                span,
+            };
+        }
+        Ok(expr)
+    }
+
     fn comparison(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.additive()?;
 
@@ -298,10 +628,12 @@ impl Parser {
             ) {
                 let operator = self.advance().unwrap().kind.clone();
                 let right = self.additive()?;
+                let span = expr.span().to(right.span());
                 expr = Expr::Binary {
                     left: Box::new(expr),
                     operator,
                     right: Box::new(right),
+                    span,
                 };
             } else {
                 break;
@@ -317,10 +649,12 @@ impl Parser {
             if matches!(t.kind, Token::Plus | Token::Minus) {
                 let operator = self.advance().unwrap().kind.clone();
                 let right = self.multiplicative()?;
+                let span = expr.span().to(right.span());
                 expr = Expr::Binary {
                     left: Box::new(expr),
                     operator,
                     right: Box::new(right),
+                    span,
                 };
             } else {
                 break;
@@ -333,13 +667,15 @@ impl Parser {
         let mut expr = self.unary()?;
 
         while let Some(t) = self.peek() {
-            if matches!(t.kind, Token::Multiply | Token::Divide) {
+            if matches!(t.kind, Token::Multiply | Token::Divide | Token::Modulo) {
                 let operator = self.advance().unwrap().kind.clone();
                 let right = self.unary()?;
+                let span = expr.span().to(right.span());
                 expr = Expr::Binary {
                     left: Box::new(expr),
                     operator,
                     right: Box::new(right),
+                    span,
                 };
             } else {
                 break;
@@ -352,11 +688,14 @@ impl Parser {
         if let Some(t) = self.peek() {
             match t.kind {
                 Token::Minus | Token::Not => {
+                    let span = Span::from(t);
                     let operator = self.advance().unwrap().kind.clone();
                     let right = self.unary()?;
+                    let span = span.to(right.span());
                     return Ok(Expr::Unary {
                         operator,
                         right: Box::new(right),
+                        span,
                     });
                 }
                 _ => {}
@@ -367,29 +706,39 @@ impl Parser {
     }
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
-        let token = self.peek().expect("Se esperaba un token");
+        let token = self
+            .peek()
+            .ok_or_else(|| self.error_at_current(MessageId::UnexpectedEndOfInput))?;
 
-        let token_kind = self.peek().unwrap().kind.clone();
+        let token_kind = token.kind.clone();
+        let span = Span::from(token);
         match token_kind {
             // --- Manejo de Paréntesis ---
             Token::LeftParen => {
                 self.advance();
                 let expr = self.parse_expression()?;
-                self.consume(Token::RightParen, "Se esperaba ')' después de la expresión")?;
+                self.consume(Token::RightParen, MessageId::ExpectedParenAfterExpression)?;
                 Ok(expr)
             }
 
             // --- Literales y Variables ---
-            Token::IntegerLiteral(_) | Token::FloatLiteral(_) | Token::StringLiteral(_) => {
+            Token::IntegerLiteral(_)
+            | Token::FloatLiteral(_)
+            | Token::StringLiteral(_)
+            | Token::BoolLiteral(_)
+            | Token::CharLiteral(_) => {
                 let t = self.advance().unwrap();
-                Ok(Expr::Literal(t.kind.clone()))
+                Ok(Expr::Literal {
+                    value: t.kind.clone(),
+                    span,
+                })
             }
 
             Token::Identifier(name) => {
                 self.advance();
 
                 if self.check(Token::LeftParen) {
-                    self.advance(); 
+                    self.advance();
                     let mut arguments = Vec::new();
 
                     if !self.check(Token::RightParen) {
@@ -401,51 +750,73 @@ impl Parser {
                             self.advance();
                         }
                     }
-                    self.consume(Token::RightParen, "Se esperaba ')'")?;
+                    self.consume(Token::RightParen, MessageId::ExpectedRightParen)?;
 
                     Ok(Expr::Call {
                         callee: name.clone(),
                         arguments,
+                        span: self.span_from(span),
                     })
                 } else {
-                    Ok(Expr::Variable(name.clone()))
+                    Ok(Expr::Variable {
+                        name: name.clone(),
+                        span,
+                    })
                 }
             }
             Token::Input => {
                 self.advance();
-                self.consume(Token::LeftParen, "Se esperaba '('")?;
-                self.consume(Token::RightParen, "Se esperaba ')'")?;
-                Ok(Expr::Input)
-            }
-            _ => {
-                return Err(ParseError::new(
-                    "Se esperaba una expresión",
-                    token.line,
-                    token.col,
-                ));
+                self.consume(Token::LeftParen, MessageId::ExpectedLeftParen)?;
+                self.consume(Token::RightParen, MessageId::ExpectedRightParen)?;
+                Ok(Expr::Input {
+                    span: self.span_from(span),
+                })
             }
+            _ => Err(ParseError::at(MessageId::ExpectedExpression, token)),
         }
     }
 
     fn check(&self, kind: Token) -> bool {
-        self.peek().map_or(false, |t| {
-            std::mem::discriminant(&t.kind) == std::mem::discriminant(&kind)
-        })
+        self.peek()
+            .is_some_and(|t| std::mem::discriminant(&t.kind) == std::mem::discriminant(&kind))
     }
 
-    fn consume(&mut self, kind: Token, msg: &str) -> Result<&TokenData, ParseError> {
+    fn consume(
+        &mut self,
+        kind: Token,
+        message: impl Into<Message>,
+    ) -> Result<&TokenData, ParseError> {
         if self.check(kind) {
             Ok(self.advance().unwrap())
         } else {
             let t = self
                 .peek()
-                .ok_or(ParseError::new("Unexpected end of input", 0, 0))?;
-            Err(ParseError::new(msg, t.line, t.col))
+                .ok_or_else(|| self.error_at_current(MessageId::UnexpectedEndOfInput))?;
+            Err(ParseError::at(message, t))
+        }
+    }
+
+    fn error_at_current(&self, message: impl Into<Message>) -> ParseError {
+        match self.peek().or(self.tokens.last()) {
+            Some(t) => ParseError::at(message, t),
+            None => ParseError::new(message, 1, 1),
+        }
+    }
+
+    fn current_span(&self) -> Span {
+        self.peek().map(Span::from).unwrap_or_default()
+    }
+
+    // Extiende `start` hasta el final del último token consumido
+    fn span_from(&self, start: Span) -> Span {
+        match self.current.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(token) => start.to(Span::from(token)),
+            None => start,
         }
     }
 
     fn is_at_end(&self) -> bool {
-        self.peek().map_or(true, |t| t.kind == Token::EOF)
+        self.peek().is_none_or(|t| t.kind == Token::EOF)
     }
 
     fn advance(&mut self) -> Option<&TokenData> {
@@ -467,11 +838,12 @@ impl Parser {
     }
 
     fn parse_switch_statement(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.current_span();
         self.advance();
-        self.consume(Token::LeftParen, "Se esperaba '('")?;
+        self.consume(Token::LeftParen, MessageId::ExpectedLeftParen)?;
         let condition = self.parse_expression()?;
-        self.consume(Token::RightParen, "Se esperaba ')'")?;
-        self.consume(Token::LeftBrace, "Se esperaba '{'")?;
+        self.consume(Token::RightParen, MessageId::ExpectedRightParen)?;
+        self.consume(Token::LeftBrace, MessageId::ExpectedLeftBrace)?;
 
         let mut cases = Vec::new();
         let mut default_case = None;
@@ -480,28 +852,27 @@ impl Parser {
             if self.check(Token::Case) {
                 self.advance();
                 let case_val = self.parse_expression()?;
-                self.consume(Token::Colon, "Se esperaba ':'")?;
-                let body = self.parse_block()?;
+                self.consume(Token::Colon, MessageId::ExpectedColon)?;
+                let body = self.parse_case_body()?;
                 cases.push((case_val, body));
             } else if self.check(Token::Default) {
                 self.advance();
-                self.consume(Token::Colon, "Se esperaba ':'")?;
-                default_case = Some(self.parse_block()?);
+                self.consume(Token::Colon, MessageId::ExpectedColon)?;
+                default_case = Some(self.parse_case_body()?);
             } else {
-                let t = self.peek().unwrap();
-                return Err(ParseError::new(
-                    "Se esperaba 'case' o 'default' dentro del switch",
-                    t.line,
-                    t.col,
-                ));
+                return Err(self.error_at_current(Message::new(
+                    MessageId::ExpectedCase,
+                    vec![Token::Case, Token::Default, Token::Switch],
+                )));
             }
         }
 
-        self.consume(Token::RightBrace, "Se esperaba '}'")?;
+        self.consume(Token::RightBrace, MessageId::ExpectedRightBrace)?;
         Ok(Stmt::Switch {
             condition,
             cases,
             default: default_case,
+            span: self.span_from(span),
         })
     }
 }
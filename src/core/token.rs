@@ -65,6 +65,8 @@ pub enum Token {
     Switch,
     Case,
     Default,
+    Break,
+    Continue,
 
     // Functions
     Function,
@@ -73,20 +75,91 @@ pub enum Token {
     Assign,
     Semi,
     EOF,
+    // Fragmento que el lexer no pudo reconocer; el error queda registrado aparte
+    Error(String),
 
     Print,
     Println,
-    Input
+    Input,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+}
+
+// Rango en bytes del texto original
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+// `leading` y `trailing` solo se rellenan en el modo sin pérdida del lexer:
+// la trivia final llega hasta el salto de línea y el resto precede al token
+// siguiente
 pub struct TokenData {
     pub kind: Token,
     pub line: usize,
     pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub start: usize,
+    pub end: usize,
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
 }
 
 impl TokenData {
     pub fn new(kind: Token, line: usize, col: usize) -> Self {
-        Self { kind, line, col }
+        Self {
+            kind,
+            line,
+            col,
+            end_line: line,
+            end_col: col,
+            start: 0,
+            end: 0,
+            leading: Vec::new(),
+            trailing: Vec::new(),
+        }
     }
-}
\ No newline at end of file
+}
+
+impl Token {
+    // Texto fijo de operadores y signos; las palabras dependen del dialecto
+    pub fn symbol(&self) -> Option<&'static str> {
+        let symbol = match self {
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Multiply => "*",
+            Token::Divide => "/",
+            Token::Modulo => "%",
+            Token::Power => "^",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::LeftParen => "(",
+            Token::RightParen => ")",
+            Token::LeftBracket => "[",
+            Token::RightBracket => "]",
+            Token::LeftBrace => "{",
+            Token::RightBrace => "}",
+            Token::Comma => ",",
+            Token::Colon => ":",
+            Token::EqualEqual => "==",
+            Token::NotEqual => "!=",
+            Token::Greater => ">",
+            Token::Less => "<",
+            Token::GreaterEqual => ">=",
+            Token::LessEqual => "<=",
+            Token::Not => "!",
+            Token::Assign => "=",
+            Token::Semi => ";",
+            _ => return None,
+        };
+        Some(symbol)
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::core::messages::{Message, MessageId};
+use crate::core::token::{Token, TokenData};
+use crate::persistence::models::SyntaxConfig;
+
+#[derive(Debug, Clone)]
+pub struct TranslateError {
+    pub message: Message,
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+}
+
+impl TranslateError {
+    fn at(message: Message, token: &TokenData) -> Self {
+        Self {
+            message,
+            line: token.line,
+            column: token.col,
+            end_column: token.end_col.max(token.col + 1),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        "E601"
+    }
+}
+
+// Reescribe `source` cambiando solo las palabras del dialecto; los tokens deben
+// venir del lexer configurado con `from`. Lo que queda entre tokens
+// (espacios, comentarios) se copia tal cual gracias a los rangos en bytes
+pub fn translate(
+    source: &str,
+    tokens: &[TokenData],
+    from: &SyntaxConfig,
+    to: &SyntaxConfig,
+) -> Result<String, Vec<TranslateError>> {
+    let targets: HashMap<&str, &str> = to.entries().into_iter().collect();
+    let replacements: HashMap<&str, &str> = from
+        .entries()
+        .into_iter()
+        .map(|(key, word)| (word, targets[key]))
+        .collect();
+    let reserved: HashMap<&str, &str> = to
+        .entries()
+        .into_iter()
+        .map(|(key, word)| (word, key))
+        .collect();
+
+    let mut output = String::with_capacity(source.len());
+    let mut errors = Vec::new();
+    let mut copied = 0;
+    for token in tokens {
+        let text = &source[token.start..token.end];
+        let replacement = match &token.kind {
+            Token::Identifier(name) => {
+                if let Some(key) = reserved.get(name.as_str()) {
+                    errors.push(TranslateError::at(
+                        Message::new(
+                            MessageId::ReservedIdentifier,
+                            vec![name.clone(), key.to_string()],
+                        ),
+                        token,
+                    ));
+                }
+                continue;
+            }
+            Token::EOF | Token::Error(_) => continue,
+            _ => match replacements.get(text) {
+                Some(&word) => word,
+                None => continue,
+            },
+        };
+        output.push_str(&source[copied..token.start]);
+        output.push_str(replacement);
+        copied = token.end;
+    }
+    output.push_str(&source[copied..]);
+
+    if errors.is_empty() {
+        Ok(output)
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,604 @@
+use std::collections::HashMap;
+
+use crate::core::ast::{Expr, Span, Stmt};
+use crate::core::messages::{Arg, Message, MessageId};
+use crate::core::token::Token;
+
+#[derive(Debug, Clone)]
+pub struct TypeError {
+    pub message: Message,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+impl TypeError {
+    pub fn new(message: impl Into<Message>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            line: span.start.line,
+            column: span.start.column,
+            span,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.message.id {
+            MessageId::UndefinedVariable | MessageId::AssignToUndefined => "E301",
+            MessageId::UndefinedFunction => "E302",
+            MessageId::TypeMismatch => "E303",
+            MessageId::ArityMismatch => "E304",
+            MessageId::IncompatibleOperands
+            | MessageId::InvalidOperandType
+            | MessageId::LogicalOperandNotBool => "E305",
+            MessageId::ConditionNotBool | MessageId::IncomparableCase => "E306",
+            MessageId::MissingInitializer => "E307",
+            MessageId::InvalidVariableType => "E308",
+            _ => "E309",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSignature {
+    pub params: Vec<Token>,
+    pub return_type: Token,
+}
+
+// Los tipos inferidos son `Option<Token>`: `None` significa "desconocido"
+// (por ejemplo `input()` o una expresión que ya produjo un error).
+#[derive(Clone)]
+pub struct TypeChecker {
+    scopes: Vec<HashMap<String, Token>>,
+    functions: HashMap<String, FunctionSignature>,
+    return_types: Vec<Token>,
+    errors: Vec<TypeError>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            return_types: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // Funciones que provee el anfitrión, como las nativas del intérprete
+    pub fn declare_function(&mut self, name: &str, signature: FunctionSignature) {
+        self.functions.insert(name.to_string(), signature);
+    }
+
+    // Lo declarado sigue visible en las llamadas siguientes, como en el REPL
+    pub fn check(&mut self, statements: &[Stmt]) -> Result<(), Vec<TypeError>> {
+        self.collect_functions(statements);
+        self.check_statements(statements);
+        self.take_errors()
+    }
+
+    pub fn check_expression(&mut self, expr: &Expr) -> Result<(), Vec<TypeError>> {
+        self.infer(expr);
+        self.take_errors()
+    }
+
+    fn take_errors(&mut self) -> Result<(), Vec<TypeError>> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.sort_by_key(|e| (e.line, e.column));
+        Err(errors)
+    }
+
+    // Las funciones se registran en un mapa global al ejecutarse, así que
+    // cualquier declaración (incluso anidada) puede ser llamada por su nombre.
+    fn collect_functions(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            match stmt {
+                Stmt::Function {
+                    return_type,
+                    name,
+                    params,
+                    body,
+                    ..
+                } => {
+                    self.functions.insert(
+                        name.clone(),
+                        FunctionSignature {
+                            params: params.iter().map(|(ty, _)| ty.clone()).collect(),
+                            return_type: return_type.clone(),
+                        },
+                    );
+                    self.collect_functions(body);
+                }
+                Stmt::Block { statements, .. } => self.collect_functions(statements),
+                Stmt::If {
+                    then_branch,
+                    else_branch,
+                    ..
+                } => {
+                    self.collect_functions(then_branch);
+                    if let Some(branch) = else_branch {
+                        self.collect_functions(branch);
+                    }
+                }
+                Stmt::While { body, .. } | Stmt::For { body, .. } => self.collect_functions(body),
+                Stmt::Switch { cases, default, .. } => {
+                    for (_, body) in cases {
+                        self.collect_functions(body);
+                    }
+                    if let Some(body) = default {
+                        self.collect_functions(body);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Los cuerpos de función se revisan al final del bloque que los declara:
+    // en ejecución ven todo lo que ese ámbito declare antes de ser llamadas.
+    fn check_statements(&mut self, statements: &[Stmt]) {
+        let mut deferred = Vec::new();
+        for stmt in statements {
+            if let Stmt::Function { .. } = stmt {
+                deferred.push(stmt);
+            } else {
+                self.check_stmt(stmt);
+            }
+        }
+        for stmt in deferred {
+            self.check_function(stmt);
+        }
+    }
+
+    fn check_scoped(&mut self, statements: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        self.check_statements(statements);
+        self.scopes.pop();
+    }
+
+    fn check_function(&mut self, stmt: &Stmt) {
+        if let Stmt::Function {
+            return_type,
+            name,
+            params,
+            body,
+            span,
+        } = stmt
+        {
+            let mut scope = HashMap::new();
+            for (ty, name) in params {
+                scope.insert(name.clone(), ty.clone());
+            }
+            self.scopes.push(scope);
+            self.return_types.push(return_type.clone());
+            self.check_statements(body);
+            self.return_types.pop();
+            self.scopes.pop();
+            if *return_type != Token::VoidType && !Self::always_returns(body) {
+                self.error(
+                    Message::new(
+                        MessageId::MissingReturn,
+                        vec![Arg::from(name.clone()), Arg::Word(return_type.clone())],
+                    ),
+                    *span,
+                );
+            }
+        }
+    }
+
+    // Los bucles solo cuentan si nunca terminan: condición `true` y sin `break`
+    fn always_returns(statements: &[Stmt]) -> bool {
+        for stmt in statements {
+            let returns = match stmt {
+                Stmt::Return { .. } => true,
+                Stmt::Break { .. } | Stmt::Continue { .. } => return false,
+                Stmt::Block { statements, .. } => Self::always_returns(statements),
+                Stmt::If {
+                    then_branch,
+                    else_branch: Some(else_branch),
+                    ..
+                } => Self::always_returns(then_branch) && Self::always_returns(else_branch),
+                Stmt::While {
+                    condition, body, ..
+                } => Self::is_true(Some(condition)) && !Self::breaks(body),
+                Stmt::For {
+                    condition, body, ..
+                } => Self::is_true(condition.as_ref()) && !Self::breaks(body),
+                Stmt::Switch {
+                    cases,
+                    default: Some(default),
+                    ..
+                } => {
+                    cases.iter().all(|(_, body)| Self::always_returns(body))
+                        && Self::always_returns(default)
+                }
+                _ => false,
+            };
+            if returns {
+                return true;
+            }
+        }
+        false
+    }
+
+    // Un `break` dentro de un bucle o switch anidado no sale del bucle actual
+    fn breaks(statements: &[Stmt]) -> bool {
+        statements.iter().any(|stmt| match stmt {
+            Stmt::Break { .. } => true,
+            Stmt::Block { statements, .. } => Self::breaks(statements),
+            Stmt::If {
+                then_branch,
+                else_branch,
+                ..
+            } => Self::breaks(then_branch) || else_branch.as_deref().is_some_and(Self::breaks),
+            _ => false,
+        })
+    }
+
+    fn is_true(condition: Option<&Expr>) -> bool {
+        matches!(
+            condition,
+            None | Some(Expr::Literal {
+                value: Token::BoolLiteral(true),
+                ..
+            })
+        )
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::VarDeclaration {
+                ty,
+                name,
+                initializer,
+                span,
+            } => {
+                if *ty == Token::VoidType {
+                    self.error(
+                        Message::new(
+                            MessageId::InvalidVariableType,
+                            vec![Arg::from(name.clone()), Arg::Word(ty.clone())],
+                        ),
+                        *span,
+                    );
+                }
+                match initializer {
+                    Some(expr) => {
+                        let actual = self.infer(expr);
+                        self.expect_assignable(ty, actual, expr.span());
+                    }
+                    None => self.error(
+                        Message::new(MessageId::MissingInitializer, vec![name.clone()]),
+                        *span,
+                    ),
+                }
+                self.declare(name, ty.clone());
+            }
+            Stmt::Assignment { name, value, span } => {
+                let actual = self.infer(value);
+                match self.lookup(name) {
+                    Some(expected) => self.expect_assignable(&expected, actual, value.span()),
+                    None => self.error(
+                        Message::new(MessageId::AssignToUndefined, vec![name.clone()]),
+                        *span,
+                    ),
+                }
+            }
+            Stmt::Expression { expr, .. } => {
+                self.infer(expr);
+            }
+            Stmt::Print { value, .. } | Stmt::Println { value, .. } => {
+                self.infer(value);
+            }
+            Stmt::Return { value, span } => self.check_return(value.as_ref(), *span),
+            Stmt::Break { .. } | Stmt::Continue { .. } => {}
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expect_condition(condition);
+                self.check_scoped(then_branch);
+                if let Some(branch) = else_branch {
+                    self.check_scoped(branch);
+                }
+            }
+            Stmt::While {
+                condition, body, ..
+            } => {
+                self.expect_condition(condition);
+                self.check_scoped(body);
+            }
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                self.scopes.push(HashMap::new());
+                if let Some(init) = initializer {
+                    self.check_stmt(init);
+                }
+                if let Some(condition) = condition {
+                    self.expect_condition(condition);
+                }
+                if let Some(increment) = increment {
+                    self.check_stmt(increment);
+                }
+                self.check_scoped(body);
+                self.scopes.pop();
+            }
+            Stmt::Block { statements, .. } => self.check_scoped(statements),
+            Stmt::Switch {
+                condition,
+                cases,
+                default,
+                ..
+            } => {
+                let expected = self.infer(condition);
+                for (case_expr, body) in cases {
+                    let actual = self.infer(case_expr);
+                    if let (Some(expected), Some(actual)) = (&expected, &actual)
+                        && !Self::comparable(expected, actual)
+                    {
+                        self.error(
+                            Message::new(
+                                MessageId::IncomparableCase,
+                                vec![actual.clone(), expected.clone()],
+                            ),
+                            case_expr.span(),
+                        );
+                    }
+                    self.check_scoped(body);
+                }
+                if let Some(body) = default {
+                    self.check_scoped(body);
+                }
+            }
+            Stmt::Function { .. } => self.check_function(stmt),
+        }
+    }
+
+    fn check_return(&mut self, value: Option<&Expr>, span: Span) {
+        let Some(expected) = self.return_types.last().cloned() else {
+            self.error(
+                Message::new(MessageId::ReturnOutsideFunction, vec![Token::Return]),
+                span,
+            );
+            if let Some(expr) = value {
+                self.infer(expr);
+            }
+            return;
+        };
+
+        match (value, expected) {
+            (None, Token::VoidType) => {}
+            (None, expected) => self.error(
+                Message::new(MessageId::ExpectedReturnValue, vec![expected]),
+                span,
+            ),
+            (Some(expr), Token::VoidType) => {
+                self.infer(expr);
+                self.error(
+                    Message::new(MessageId::VoidReturnsValue, vec![Token::VoidType]),
+                    expr.span(),
+                );
+            }
+            (Some(expr), expected) => {
+                let actual = self.infer(expr);
+                self.expect_assignable(&expected, actual, expr.span());
+            }
+        }
+    }
+
+    fn expect_condition(&mut self, condition: &Expr) {
+        let actual = self.infer(condition);
+        if let Some(actual) = actual
+            && actual != Token::BoolType
+        {
+            self.error(
+                Message::new(MessageId::ConditionNotBool, vec![Token::BoolType, actual]),
+                condition.span(),
+            );
+        }
+    }
+
+    fn expect_assignable(&mut self, expected: &Token, actual: Option<Token>, span: Span) {
+        if let Some(actual) = actual
+            && actual != *expected
+        {
+            self.error(
+                Message::new(MessageId::TypeMismatch, vec![expected.clone(), actual]),
+                span,
+            );
+        }
+    }
+
+    fn infer(&mut self, expr: &Expr) -> Option<Token> {
+        match expr {
+            Expr::Literal { value, .. } => match value {
+                Token::IntegerLiteral(_) => Some(Token::IntType),
+                Token::FloatLiteral(_) => Some(Token::FloatType),
+                Token::StringLiteral(_) => Some(Token::StringType),
+                Token::CharLiteral(_) => Some(Token::CharType),
+                Token::BoolLiteral(_) => Some(Token::BoolType),
+                _ => None,
+            },
+            Expr::Variable { name, span } => {
+                let ty = self.lookup(name);
+                if ty.is_none() {
+                    self.error(
+                        Message::new(MessageId::UndefinedVariable, vec![name.clone()]),
+                        *span,
+                    );
+                }
+                ty
+            }
+            Expr::Input { .. } => None,
+            Expr::Unary {
+                operator,
+                right,
+                span,
+            } => {
+                let operand = self.infer(right)?;
+                match (operator, &operand) {
+                    (
+                        Token::Minus,
+                        Token::IntType | Token::BigIntType | Token::FloatType | Token::DoubleType,
+                    ) => Some(operand),
+                    (Token::Not, Token::BoolType) => Some(Token::BoolType),
+                    _ => {
+                        self.error(
+                            Message::new(
+                                MessageId::InvalidOperandType,
+                                vec![operator.clone(), operand],
+                            ),
+                            *span,
+                        );
+                        None
+                    }
+                }
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+                span,
+            } => {
+                let l = self.infer(left);
+                let r = self.infer(right);
+                let (l, r) = (l?, r?);
+                let result = Self::binary_result(&l, operator, &r);
+                if result.is_none() {
+                    self.error(
+                        Message::new(
+                            MessageId::IncompatibleOperands,
+                            vec![l.clone(), operator.clone(), r.clone()],
+                        ),
+                        *span,
+                    );
+                }
+                result
+            }
+            Expr::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                for operand in [left, right] {
+                    if let Some(ty) = self.infer(operand)
+                        && ty != Token::BoolType
+                    {
+                        self.error(
+                            Message::new(
+                                MessageId::LogicalOperandNotBool,
+                                vec![operator.clone(), Token::BoolType, ty],
+                            ),
+                            operand.span(),
+                        );
+                    }
+                }
+                Some(Token::BoolType)
+            }
+            Expr::Call {
+                callee,
+                arguments,
+                span,
+            } => {
+                let arg_types: Vec<Option<Token>> =
+                    arguments.iter().map(|arg| self.infer(arg)).collect();
+
+                let Some(signature) = self.functions.get(callee).cloned() else {
+                    self.error(
+                        Message::new(MessageId::UndefinedFunction, vec![callee.clone()]),
+                        *span,
+                    );
+                    return None;
+                };
+
+                if signature.params.len() != arguments.len() {
+                    self.error(
+                        Message::new(
+                            MessageId::ArityMismatch,
+                            vec![
+                                callee.clone(),
+                                signature.params.len().to_string(),
+                                arguments.len().to_string(),
+                            ],
+                        ),
+                        *span,
+                    );
+                } else {
+                    for ((expected, actual), arg) in
+                        signature.params.iter().zip(arg_types).zip(arguments)
+                    {
+                        self.expect_assignable(expected, actual, arg.span());
+                    }
+                }
+                Some(signature.return_type)
+            }
+        }
+    }
+
+    fn binary_result(left: &Token, op: &Token, right: &Token) -> Option<Token> {
+        match op {
+            Token::EqualEqual | Token::NotEqual => Some(Token::BoolType),
+            Token::Greater | Token::Less | Token::GreaterEqual | Token::LessEqual => {
+                Self::numeric_result(left, right).map(|_| Token::BoolType)
+            }
+            Token::Plus if *left == Token::StringType && *right == Token::StringType => {
+                Some(Token::StringType)
+            }
+            Token::Plus | Token::Minus | Token::Multiply | Token::Divide | Token::Modulo => {
+                Self::numeric_result(left, right)
+            }
+            _ => None,
+        }
+    }
+
+    // Promoción numérica: el resultado toma el tipo más amplio de los operandos
+    fn numeric_result(left: &Token, right: &Token) -> Option<Token> {
+        let rank = |ty: &Token| match ty {
+            Token::IntType => Some(0),
+            Token::BigIntType => Some(1),
+            Token::FloatType => Some(2),
+            Token::DoubleType => Some(3),
+            _ => None,
+        };
+        let (l, r) = (rank(left)?, rank(right)?);
+        Some(if l >= r { left.clone() } else { right.clone() })
+    }
+
+    fn comparable(left: &Token, right: &Token) -> bool {
+        left == right || Self::numeric_result(left, right).is_some()
+    }
+
+    fn declare(&mut self, name: &str, ty: Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), ty);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Token> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    fn error(&mut self, message: impl Into<Message>, span: Span) {
+        self.errors.push(TypeError::new(message, span));
+    }
+}
@@ -0,0 +1,338 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::compiler::{Capture, FunctionProto, Instruction, Program};
+use crate::core::interpreter::{
+    DEFAULT_MAX_CALL_DEPTH, Interpreter, RuntimeError, RuntimeErrorKind, StackFrame, Value,
+    Variable,
+};
+use crate::core::io::{Io, StdIo};
+use crate::core::token::Token;
+use crate::persistence::models::SyntaxConfig;
+
+struct Closure {
+    proto: Rc<FunctionProto>,
+    upvalues: Vec<Rc<RefCell<Value>>>,
+}
+
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+    base: usize,
+    cells: Vec<Rc<RefCell<Value>>>,
+}
+
+pub struct Vm {
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    globals: Vec<Option<Variable>>,
+    functions: Vec<Option<Rc<Closure>>>,
+    max_call_depth: usize,
+    io: Box<dyn Io>,
+    pub config: SyntaxConfig,
+}
+
+impl Vm {
+    pub fn new(config: SyntaxConfig) -> Self {
+        Self {
+            stack: Vec::new(),
+            frames: Vec::new(),
+            globals: Vec::new(),
+            functions: Vec::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            io: Box::new(StdIo),
+            config,
+        }
+    }
+
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub fn set_io(&mut self, io: impl Io + 'static) {
+        self.io = Box::new(io);
+    }
+
+    pub fn run(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        self.globals.resize(program.names.len(), None);
+        self.functions.resize(program.names.len(), None);
+        self.frames.push(Frame {
+            closure: Rc::new(Closure {
+                proto: program.main.clone(),
+                upvalues: Vec::new(),
+            }),
+            ip: 0,
+            base: 0,
+            cells: Vec::new(),
+        });
+
+        let result = self
+            .execute(program)
+            .map_err(|kind| self.error(kind, program));
+        let flushed = self.io.flush();
+        self.frames.clear();
+        self.stack.clear();
+        result?;
+        flushed.map_err(|e| Interpreter::error_at(e.into(), Default::default()))
+    }
+
+    // Cada marco por encima del principal aporta una entrada a la traza,
+    // con el sitio de la llamada tomado del marco que la hizo
+    fn error(&self, kind: RuntimeErrorKind, program: &Program) -> RuntimeError {
+        let span_of = |frame: &Frame| frame.closure.proto.chunk.spans[frame.ip - 1];
+        let mut error = Interpreter::error_at(kind, span_of(self.frame()));
+        for pair in self.frames.windows(2).rev() {
+            error.call_stack.push(StackFrame {
+                function: program.names[pair[1].closure.proto.name as usize].clone(),
+                call_site: span_of(&pair[0]),
+            });
+        }
+        error
+    }
+
+    fn execute(&mut self, program: &Program) -> Result<(), RuntimeErrorKind> {
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            let Some(&instruction) = frame.closure.proto.chunk.code.get(frame.ip) else {
+                return Ok(());
+            };
+            frame.ip += 1;
+
+            match instruction {
+                Instruction::Constant(index) => {
+                    let value = self.frame().closure.proto.chunk.constants[index as usize].clone();
+                    self.stack.push(value);
+                }
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::PopN(count) => {
+                    let len = self.stack.len() - count as usize;
+                    self.stack.truncate(len);
+                }
+
+                Instruction::GetLocal(slot) => {
+                    let value = self.stack[self.frame().base + slot as usize].clone();
+                    self.stack.push(value);
+                }
+                Instruction::SetLocal(slot) => {
+                    let value = self.pop();
+                    let index = self.frame().base + slot as usize;
+                    self.stack[index] = value;
+                }
+                Instruction::NewCell(cell) => {
+                    let cells = &mut self.frames.last_mut().unwrap().cells;
+                    let cell = cell as usize;
+                    if cell >= cells.len() {
+                        cells.resize_with(cell + 1, || Rc::new(RefCell::new(Value::Void)));
+                    }
+                    cells[cell] = Rc::new(RefCell::new(Value::Void));
+                }
+                // Una celda creada al entrar al ámbito puede estar ya capturada
+                Instruction::MakeCell(cell) => {
+                    let value = self.peek().clone();
+                    let cells = &mut self.frames.last_mut().unwrap().cells;
+                    let cell = cell as usize;
+                    if cell >= cells.len() {
+                        cells.resize_with(cell + 1, || Rc::new(RefCell::new(Value::Void)));
+                    }
+                    *cells[cell].borrow_mut() = value;
+                }
+                Instruction::GetCell(cell) => {
+                    let value = self.frame().cells[cell as usize].borrow().clone();
+                    self.stack.push(value);
+                }
+                Instruction::SetCell(cell) => {
+                    let value = self.pop();
+                    *self.frame().cells[cell as usize].borrow_mut() = value;
+                }
+                Instruction::GetUpvalue(index) => {
+                    let value = self.frame().closure.upvalues[index as usize]
+                        .borrow()
+                        .clone();
+                    self.stack.push(value);
+                }
+                Instruction::SetUpvalue(index) => {
+                    let value = self.pop();
+                    *self.frame().closure.upvalues[index as usize].borrow_mut() = value;
+                }
+                Instruction::DefineGlobal(name, ty) => {
+                    let value = self.pop();
+                    let var_type = self.frame().closure.proto.chunk.types[ty as usize].clone();
+                    Interpreter::type_check(&var_type, &value)?;
+                    self.globals[name as usize] = Some(Variable { var_type, value });
+                }
+                Instruction::GetGlobal(name) => {
+                    let value = self.globals[name as usize]
+                        .as_ref()
+                        .map(|v| v.value.clone())
+                        .ok_or_else(|| {
+                            RuntimeErrorKind::UndefinedVariable(
+                                program.names[name as usize].clone(),
+                            )
+                        })?;
+                    self.stack.push(value);
+                }
+                Instruction::SetGlobal(name) => {
+                    let value = self.pop();
+                    match self.globals[name as usize].as_mut() {
+                        Some(var) => {
+                            Interpreter::type_check(&var.var_type, &value)?;
+                            var.value = value;
+                        }
+                        None => {
+                            return Err(RuntimeErrorKind::UndefinedVariable(
+                                program.names[name as usize].clone(),
+                            ));
+                        }
+                    }
+                }
+                Instruction::CheckType(ty) => {
+                    let expected = &self.frame().closure.proto.chunk.types[ty as usize];
+                    Interpreter::type_check(expected, self.peek())?;
+                }
+
+                Instruction::Negate => {
+                    let value = self.pop();
+                    let result = Interpreter::execute_unary_op(&Token::Minus, value)?;
+                    self.stack.push(result);
+                }
+                Instruction::Not => {
+                    let value = self.pop();
+                    let result = Interpreter::execute_unary_op(&Token::Not, value)?;
+                    self.stack.push(result);
+                }
+                Instruction::Add => self.binary(Token::Plus)?,
+                Instruction::Subtract => self.binary(Token::Minus)?,
+                Instruction::Multiply => self.binary(Token::Multiply)?,
+                Instruction::Divide => self.binary(Token::Divide)?,
+                Instruction::Modulo => self.binary(Token::Modulo)?,
+                Instruction::Equal => self.binary(Token::EqualEqual)?,
+                Instruction::NotEqual => self.binary(Token::NotEqual)?,
+                Instruction::Greater => self.binary(Token::Greater)?,
+                Instruction::Less => self.binary(Token::Less)?,
+                Instruction::GreaterEqual => self.binary(Token::GreaterEqual)?,
+                Instruction::LessEqual => self.binary(Token::LessEqual)?,
+                Instruction::CaseEqual => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(Value::Bool(left == right));
+                }
+
+                Instruction::Jump(target) => {
+                    self.frames.last_mut().unwrap().ip = target as usize;
+                }
+                Instruction::JumpIfFalse(target) => {
+                    let condition = self.pop();
+                    if !Interpreter::is_truthy(condition) {
+                        self.frames.last_mut().unwrap().ip = target as usize;
+                    }
+                }
+                Instruction::Call(name, argc) => {
+                    let closure = self.functions[name as usize].clone().ok_or_else(|| {
+                        RuntimeErrorKind::UndefinedFunction(program.names[name as usize].clone())
+                    })?;
+                    let param_types = &closure.proto.param_types;
+                    if param_types.len() != argc as usize {
+                        return Err(RuntimeErrorKind::ArityMismatch {
+                            function: program.names[name as usize].clone(),
+                            expected: param_types.len(),
+                            found: argc as usize,
+                        });
+                    }
+                    let args = &self.stack[self.stack.len() - argc as usize..];
+                    for (ty, value) in param_types.iter().zip(args) {
+                        Interpreter::type_check(ty, value)?;
+                    }
+                    // El marco principal no cuenta como llamada
+                    if self.frames.len() > self.max_call_depth {
+                        return Err(RuntimeErrorKind::StackOverflow {
+                            limit: self.max_call_depth,
+                        });
+                    }
+                    let base = self.stack.len() - argc as usize;
+                    self.frames.push(Frame {
+                        closure,
+                        ip: 0,
+                        base,
+                        cells: Vec::new(),
+                    });
+                }
+                Instruction::Closure(index) => {
+                    let proto = program.functions[index as usize].clone();
+                    let frame = self.frame();
+                    let upvalues = proto
+                        .captures
+                        .iter()
+                        .map(|capture| match capture {
+                            Capture::Cell(cell) => frame.cells[*cell as usize].clone(),
+                            Capture::Upvalue(index) => {
+                                frame.closure.upvalues[*index as usize].clone()
+                            }
+                        })
+                        .collect();
+                    let name = proto.name as usize;
+                    self.functions[name] = Some(Rc::new(Closure { proto, upvalues }));
+                }
+                Instruction::Return => {
+                    let result = self.pop();
+                    let result =
+                        Interpreter::check_return(&self.frame().closure.proto.return_type, result)?;
+                    let frame = self.frames.pop().unwrap();
+                    self.stack.truncate(frame.base);
+                    self.stack.push(result);
+                }
+
+                Instruction::MissingReturn => {
+                    let proto = &self.frame().closure.proto;
+                    return Err(RuntimeErrorKind::MissingReturn {
+                        function: program.names[proto.name as usize].clone(),
+                        expected: proto.return_type.clone(),
+                    });
+                }
+
+                Instruction::MissingInitializer(name) => {
+                    return Err(RuntimeErrorKind::MissingInitializer(
+                        program.names[name as usize].clone(),
+                    ));
+                }
+
+                Instruction::Print => {
+                    let value = self.pop();
+                    self.io.write(&value.to_string())?;
+                    self.io.flush()?;
+                }
+                Instruction::Println => {
+                    let value = self.pop();
+                    self.io.write(&format!("{}\n", value))?;
+                }
+                Instruction::Input => {
+                    self.io.flush()?;
+                    let line = self.io.read_line()?.ok_or(RuntimeErrorKind::EndOfInput)?;
+                    let value = Interpreter::parse_input(&line, &self.config);
+                    self.stack.push(value);
+                }
+            }
+        }
+    }
+
+    fn binary(&mut self, op: Token) -> Result<(), RuntimeErrorKind> {
+        let right = self.pop();
+        let left = self.pop();
+        let result = Interpreter::execute_binary_op(left, op, right)?;
+        self.stack.push(result);
+        Ok(())
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().unwrap()
+    }
+
+    fn peek(&self) -> &Value {
+        self.stack.last().unwrap()
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap()
+    }
+}
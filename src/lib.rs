@@ -4,9 +4,22 @@ pub mod core {
     pub mod parser;
     pub mod ast;
     pub mod interpreter;
+    pub mod type_checker;
+    pub mod compiler;
+    pub mod vm;
+    pub mod diagnostics;
+    pub mod io;
+    pub mod messages;
+    pub mod translator;
 }
 
 pub mod persistence {
     pub mod loader;
+    pub mod languages;
     pub mod models;
-}
\ No newline at end of file
+}
+
+pub mod cli {
+    pub mod repl;
+    pub mod test_runner;
+}
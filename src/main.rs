@@ -1,33 +1,122 @@
-use std::{env, fs};
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
 
-use fabric_lang::core::interpreter::Interpreter;
+use fabric_lang::cli::repl::{self, Repl};
+use fabric_lang::cli::test_runner::{self, TestResult};
+
+use fabric_lang::core::compiler::Compiler;
+use fabric_lang::core::diagnostics::{self, Diagnostic};
+use fabric_lang::core::interpreter::{self, Interpreter};
 use fabric_lang::core::lexer::Lexer;
+use fabric_lang::core::messages::MessageCatalog;
 use fabric_lang::core::parser::Parser;
-use fabric_lang::persistence::models::CompilerConfig;
-use fabric_lang::persistence::{self};
+use fabric_lang::core::translator;
+use fabric_lang::core::type_checker::TypeChecker;
+use fabric_lang::core::vm::Vm;
+use fabric_lang::persistence::languages;
+use fabric_lang::persistence::loader::{self, ConfigOverrides, Configs};
+use fabric_lang::persistence::models::{CompilerConfig, SyntaxConfig};
+
+// Códigos de salida: errores de uso o de lectura, errores en el código fuente
+// (léxicos, sintácticos, de tipos o de compilación), errores de ejecución y
+// errores en los archivos de configuración
+const EXIT_USAGE: i32 = 64;
+const EXIT_SOURCE_ERROR: i32 = 65;
+const EXIT_RUNTIME_ERROR: i32 = 70;
+const EXIT_CONFIG: i32 = 78;
+const EXIT_TEST_FAILURE: i32 = 1;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let compiler_config: CompilerConfig =
-        persistence::loader::read_config_file("src/config/compiler.toml").unwrap();
+    let mut args: Vec<String> = env::args().collect();
+    let overrides = take_config_flags(&mut args);
+
+    if args.get(1).is_some_and(|arg| arg == "config") {
+        check_config(&args, &overrides);
+    }
+    if args.get(1).is_some_and(|arg| arg == "translate") {
+        translate_file(&args, &overrides);
+    }
+
+    // La configuración se busca a partir del directorio del programa, o del
+    // directorio actual en los modos que no reciben uno
+    let script = args
+        .get(1..)
+        .unwrap_or_default()
+        .iter()
+        .find(|arg| !arg.starts_with('-') && !matches!(arg.as_str(), "repl" | "test"));
+    let start_dir = script
+        .and_then(|path| Path::new(path).parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let Configs {
+        compiler: compiler_config,
+        syntax: syntax_config,
+        messages,
+    } = match loader::load_configs(&overrides, start_dir) {
+        Ok(configs) => configs,
+        Err(e) => {
+            eprintln!("Error de configuración: {}", e);
+            process::exit(EXIT_CONFIG);
+        }
+    };
 
-    if args.len() < 2 {
-        eprintln!("Uso: {} [-a] <archivo.{}>", args[0], compiler_config.extension);
+    // Sin archivo se abre el modo interactivo
+    if args.len() < 2 || args[1] == "repl" {
+        start_repl(&compiler_config, syntax_config, messages);
         return;
     }
 
+    if args[1] == "test" {
+        run_tests(&args[2..], &compiler_config, &overrides);
+    }
+
     // --- Detectar flags ---
     let mut show_ast = false;
+    let mut skip_check = false;
+    let mut use_vm = false;
     let mut file_path = "";
 
     for arg in &args[1..] {
         match arg.as_str() {
             "-a" => show_ast = true,
+            "-n" => skip_check = true,
+            "-b" => use_vm = true,
             "-h" => {
-                println!("Uso: {} [-a] <archivo.{}>", args[0], compiler_config.extension);
+                println!(
+                    "Uso: {} [-a] [-n] [-b] <archivo.{}>",
+                    args[0], compiler_config.extension
+                );
                 println!("Opciones:");
                 println!("  -a    Mostrar AST en lugar de ejecutar");
+                println!("  -n    Omitir la verificación de tipos antes de ejecutar");
+                println!("  -b    Ejecutar con la máquina virtual de bytecode");
                 println!("  -h    Mostrar esta ayuda");
+                println!("  --config <archivo>  Usar esta configuración del compilador");
+                println!("  --syntax <archivo>  Usar esta sintaxis");
+                println!(
+                    "  --lang <idioma>     Usar la sintaxis y los mensajes de un idioma (en, es, pt)"
+                );
+                println!();
+                println!("       {} [repl]", args[0]);
+                println!("  Sin archivo abre una sesión interactiva (:ayuda lista sus comandos)");
+                println!();
+                println!("       {} test [--bless] [-n] [-b] [rutas...]", args[0]);
+                println!("  Ejecuta los programas de las rutas (por defecto tests/) y compara");
+                println!(
+                    "  su salida con los archivos .out, .err y .exit; con --bless los actualiza"
+                );
+                println!();
+                println!(
+                    "       {} translate --from <dialecto> --to <dialecto> <archivo>",
+                    args[0]
+                );
+                println!("  Reescribe el programa en otro dialecto y lo muestra por la salida;");
+                println!("  cada dialecto es un archivo .toml o un idioma incluido (en, es, pt)");
+                println!();
+                println!("       {} config check [archivos...]", args[0]);
+                println!(
+                    "  Valida los archivos de sintaxis dados, o la configuración que se usaría"
+                );
                 return;
             }
             _ => file_path = arg,
@@ -36,7 +125,7 @@ fn main() {
 
     if file_path.is_empty() {
         eprintln!("Error: no se proporcionó archivo de entrada.");
-        return;
+        process::exit(EXIT_USAGE);
     }
 
     if !file_path.ends_with(&compiler_config.extension) {
@@ -44,42 +133,344 @@ fn main() {
             "Error: el archivo debe tener extensión .{}",
             compiler_config.extension
         );
-        return;
+        process::exit(EXIT_USAGE);
     }
 
     let input = match fs::read_to_string(file_path) {
         Ok(content) => content,
         Err(_) => {
             eprintln!("No se pudo leer el archivo {}", file_path);
-            return;
+            process::exit(EXIT_USAGE);
         }
     };
 
-    let syntax_config = persistence::loader::read_config_file("src/config/syntax.toml").unwrap();
+    let color = diagnostics::use_color();
+    let report = |diagnostic: Diagnostic| {
+        eprint!(
+            "{}",
+            diagnostic.render(file_path, &input, &messages, &syntax_config, color)
+        );
+    };
 
     let mut lexer = Lexer::new(&input, &syntax_config);
-    let tokens = match lexer.tokenize() {
-        Ok(tokens) => tokens,
-        Err(e) => {
-            eprintln!("Lexical Error: {}", e.message);
-            return;
+    let (tokens, errors) = lexer.tokenize();
+    if !errors.is_empty() {
+        for e in &errors {
+            report(Diagnostic::from(e));
         }
-    };
+        process::exit(EXIT_SOURCE_ERROR);
+    }
 
     let mut parser = Parser::new(tokens);
-    let ast = match parser.parse() {
-        Ok(ast) => ast,
-        Err(e) => {
-            eprintln!("Parser Error: {:?}", e);
-            return;
-        }
-    };
+    let (ast, errors) = parser.parse();
+    for e in &errors {
+        report(Diagnostic::from(e));
+    }
 
+    // Con -a se muestra también el AST parcial de un archivo con errores
     if show_ast {
         println!("{:#?}", ast);
         return;
     }
+    if !errors.is_empty() {
+        process::exit(EXIT_SOURCE_ERROR);
+    }
+
+    if !skip_check && let Err(errors) = TypeChecker::new().check(&ast) {
+        for e in &errors {
+            report(Diagnostic::from(e));
+        }
+        process::exit(EXIT_SOURCE_ERROR);
+    }
+
+    if use_vm {
+        let program = match Compiler::new().compile(&ast) {
+            Ok(program) => program,
+            Err(e) => {
+                report(Diagnostic::from(&e));
+                process::exit(EXIT_SOURCE_ERROR);
+            }
+        };
+        let mut vm = Vm::new(syntax_config.clone());
+        vm.set_max_call_depth(compiler_config.max_call_depth);
+        if let Err(e) = vm.run(&program) {
+            report(Diagnostic::from(&e));
+            process::exit(EXIT_RUNTIME_ERROR);
+        }
+        return;
+    }
+
+    let max_call_depth = compiler_config.max_call_depth;
+    let syntax = syntax_config.clone();
+    let result = interpreter::with_call_stack(max_call_depth, move || {
+        let mut interpreter = Interpreter::new(syntax);
+        interpreter.set_max_call_depth(max_call_depth);
+        interpreter.interpret(ast)
+    })
+    .and_then(|result| result);
+    if let Err(e) = result {
+        report(Diagnostic::from(&e));
+        process::exit(EXIT_RUNTIME_ERROR);
+    }
+}
+
+// Quita `--config <archivo>`, `--syntax <archivo>` y `--lang <idioma>` de los argumentos
+fn take_config_flags(args: &mut Vec<String>) -> ConfigOverrides {
+    let mut overrides = ConfigOverrides::default();
+    let mut i = 1;
+    while i < args.len() {
+        if !matches!(args[i].as_str(), "--config" | "--syntax" | "--lang") {
+            i += 1;
+            continue;
+        }
+        if i + 1 >= args.len() {
+            eprintln!("Error: falta el valor después de {}", args[i]);
+            process::exit(EXIT_USAGE);
+        }
+        let value = args.remove(i + 1);
+        match args.remove(i).as_str() {
+            "--config" => overrides.compiler = Some(PathBuf::from(value)),
+            "--syntax" => overrides.syntax = Some(PathBuf::from(value)),
+            _ => overrides.lang = Some(value),
+        }
+    }
+    overrides
+}
+
+// --- Subcomando `config check` ---
+fn check_config(args: &[String], overrides: &ConfigOverrides) -> ! {
+    if args.get(2).is_none_or(|arg| arg != "check") {
+        eprintln!("Uso: {} config check [archivos...]", args[0]);
+        process::exit(EXIT_USAGE);
+    }
+
+    let files = &args[3..];
+    if files.is_empty() {
+        match loader::load_configs(overrides, Path::new(".")) {
+            Ok(_) => {
+                println!("Configuración correcta");
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error de configuración: {}", e);
+                process::exit(EXIT_CONFIG);
+            }
+        }
+    }
+
+    let mut failed = false;
+    for file in files {
+        let path = Path::new(file);
+        // Un `fabric.toml` se comprueba tal como lo resolvería un programa del proyecto
+        let result = if path
+            .file_name()
+            .is_some_and(|name| name == loader::PROJECT_FILE)
+        {
+            let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+            loader::load_configs(&ConfigOverrides::default(), dir.unwrap_or(Path::new(".")))
+                .map(|_| ())
+        } else {
+            loader::read_syntax_file(path).map(|_| ())
+        };
+        match result {
+            Ok(()) => println!("{}: correcto", file),
+            Err(e) => {
+                eprintln!("Error de configuración: {}", e);
+                failed = true;
+            }
+        }
+    }
+    process::exit(if failed { EXIT_CONFIG } else { 0 });
+}
+
+// --- Subcomando `translate` ---
+fn translate_file(args: &[String], overrides: &ConfigOverrides) -> ! {
+    let usage = || -> ! {
+        eprintln!(
+            "Uso: {} translate --from <dialecto> --to <dialecto> <archivo>",
+            args[0]
+        );
+        process::exit(EXIT_USAGE);
+    };
+    let mut from = None;
+    let mut to = None;
+    let mut file_path = None;
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--from" => from = Some(rest.next().unwrap_or_else(|| usage())),
+            "--to" => to = Some(rest.next().unwrap_or_else(|| usage())),
+            _ if file_path.is_none() => file_path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let (Some(from), Some(to), Some(file_path)) = (from, to, file_path) else {
+        usage()
+    };
+
+    // Cada dialecto puede ser un archivo .toml o el nombre de un idioma incluido
+    let read_syntax = |dialect: &String| {
+        let syntax = if dialect.ends_with(".toml") {
+            loader::read_syntax_file(dialect)
+        } else {
+            languages::find_language(dialect).and_then(|pack| pack.syntax())
+        };
+        syntax.unwrap_or_else(|e| {
+            eprintln!("Error de configuración: {}", e);
+            process::exit(EXIT_CONFIG);
+        })
+    };
+    let (from, to) = (read_syntax(from), read_syntax(to));
+    let input = fs::read_to_string(file_path).unwrap_or_else(|_| {
+        eprintln!("No se pudo leer el archivo {}", file_path);
+        process::exit(EXIT_USAGE);
+    });
+
+    // Los mensajes siguen el idioma configurado, como al ejecutar el programa
+    let start_dir = Path::new(file_path)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty());
+    let messages = match loader::load_configs(overrides, start_dir.unwrap_or(Path::new("."))) {
+        Ok(configs) => configs.messages,
+        Err(e) => {
+            eprintln!("Error de configuración: {}", e);
+            process::exit(EXIT_CONFIG);
+        }
+    };
+    let color = diagnostics::use_color();
+    let report = |diagnostic: Diagnostic| {
+        eprint!(
+            "{}",
+            diagnostic.render(file_path, &input, &messages, &from, color)
+        );
+    };
+
+    let (tokens, errors) = Lexer::new(&input, &from).tokenize();
+    if !errors.is_empty() {
+        for e in &errors {
+            report(Diagnostic::from(e));
+        }
+        process::exit(EXIT_SOURCE_ERROR);
+    }
+    match translator::translate(&input, &tokens, &from, &to) {
+        Ok(output) => {
+            print!("{}", output);
+            process::exit(0);
+        }
+        Err(errors) => {
+            for e in &errors {
+                report(Diagnostic::from(e));
+            }
+            process::exit(EXIT_SOURCE_ERROR);
+        }
+    }
+}
+
+// --- Modo interactivo ---
+fn start_repl(
+    compiler_config: &CompilerConfig,
+    syntax_config: SyntaxConfig,
+    messages: MessageCatalog,
+) {
+    println!(
+        "{} {} (escriba :ayuda para ver los comandos)",
+        compiler_config.name, compiler_config.version
+    );
+
+    let max_call_depth = compiler_config.max_call_depth;
+    let (catalog, syntax) = (messages.clone(), syntax_config.clone());
+    let result = interpreter::with_call_stack(max_call_depth, move || {
+        let mut repl = Repl::new(syntax_config, max_call_depth, Some(repl::history_path()));
+        repl.set_messages(messages);
+        repl.run(std::io::stdin().lock())
+    });
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            eprintln!("Error: {}", e);
+            process::exit(EXIT_USAGE);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", catalog.translate(&e.kind.message(), &syntax));
+            process::exit(EXIT_RUNTIME_ERROR);
+        }
+    }
+}
+
+// --- Subcomando `test` ---
+fn run_tests(args: &[String], compiler_config: &CompilerConfig, overrides: &ConfigOverrides) -> ! {
+    let mut bless = false;
+    // Las rutas de configuración explícitas también valen para cada programa
+    let mut flags = Vec::new();
+    for (flag, path) in [
+        ("--config", &overrides.compiler),
+        ("--syntax", &overrides.syntax),
+    ] {
+        if let Some(path) = path {
+            flags.push(flag.to_string());
+            flags.push(path.display().to_string());
+        }
+    }
+    if let Some(lang) = &overrides.lang {
+        flags.push("--lang".to_string());
+        flags.push(lang.clone());
+    }
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            "-n" | "-b" => flags.push(arg.clone()),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("tests"));
+    }
+
+    let executable = env::current_exe().unwrap_or_else(|e| {
+        eprintln!("Error: no se encontró el ejecutable: {}", e);
+        process::exit(EXIT_USAGE);
+    });
+    let programs = test_runner::discover(&paths, &compiler_config.extension).unwrap_or_else(|e| {
+        eprintln!("Error: no se pudieron listar los programas: {}", e);
+        process::exit(EXIT_USAGE);
+    });
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for program in &programs {
+        let name = program.display();
+        match test_runner::run_test(&executable, program, &flags, bless) {
+            Ok(TestResult::Passed) => {
+                println!("test {} ... ok", name);
+                passed += 1;
+            }
+            Ok(TestResult::Blessed) => {
+                println!("test {} ... actualizado", name);
+                passed += 1;
+            }
+            Ok(TestResult::Failed(mismatches)) => {
+                println!("test {} ... FALLÓ", name);
+                for mismatch in mismatches {
+                    println!("---- {} ({}) ----", name, mismatch.stream);
+                    println!("esperado:\n{}", mismatch.expected);
+                    println!("obtenido:\n{}", mismatch.actual);
+                }
+                failed += 1;
+            }
+            Err(e) => {
+                println!("test {} ... ERROR: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
 
-    let mut interpreter = Interpreter::new(syntax_config);
-    interpreter.interpret(ast);
-}
\ No newline at end of file
+    println!();
+    println!(
+        "resultado: {}. {} correctos; {} fallidos",
+        if failed == 0 { "ok" } else { "FALLÓ" },
+        passed,
+        failed
+    );
+    process::exit(if failed == 0 { 0 } else { EXIT_TEST_FAILURE });
+}
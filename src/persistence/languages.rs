@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use crate::core::messages::MessageCatalog;
+use crate::persistence::loader::{
+    ConfigError, DEFAULT_SYNTAX_CONFIG, parse_config, validate_syntax,
+};
+use crate::persistence::models::SyntaxConfig;
+
+// Dialecto y mensajes incluidos en el binario, seleccionables con `--lang` o
+// con `lang` en compiler.toml
+pub struct LanguagePack {
+    pub name: &'static str,
+    syntax: &'static str,
+    messages: &'static str,
+}
+
+pub const LANGUAGES: &[LanguagePack] = &[
+    LanguagePack {
+        name: "en",
+        syntax: DEFAULT_SYNTAX_CONFIG,
+        messages: include_str!("../config/lang/en/messages.toml"),
+    },
+    LanguagePack {
+        name: "es",
+        syntax: include_str!("../config/lang/es/syntax.toml"),
+        messages: include_str!("../config/lang/es/messages.toml"),
+    },
+    LanguagePack {
+        name: "pt",
+        syntax: include_str!("../config/lang/pt/syntax.toml"),
+        messages: include_str!("../config/lang/pt/messages.toml"),
+    },
+];
+
+pub fn find_language(name: &str) -> Result<&'static LanguagePack, ConfigError> {
+    LANGUAGES
+        .iter()
+        .find(|pack| pack.name == name)
+        .ok_or_else(|| ConfigError::UnknownLanguage(name.to_string()))
+}
+
+impl LanguagePack {
+    pub fn syntax(&self) -> Result<SyntaxConfig, ConfigError> {
+        let path = self.path("syntax.toml");
+        validate_syntax(parse_config(self.syntax, &path)?, &path)
+    }
+
+    pub fn messages(&self) -> Result<MessageCatalog, ConfigError> {
+        parse_config(self.messages, &self.path("messages.toml"))
+    }
+
+    fn path(&self, file_name: &str) -> std::path::PathBuf {
+        Path::new("lang").join(self.name).join(file_name)
+    }
+}
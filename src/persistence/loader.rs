@@ -1,7 +1,238 @@
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
-pub fn read_config_file<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, std::io::Error>{
-    let toml_str = fs::read_to_string(path).expect("Error al leer el archivo");
-    let config: T = toml::from_str(&toml_str).expect("Error al parsear el archivo");
-    Ok(config)
-}
\ No newline at end of file
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::core::messages::MessageCatalog;
+use crate::persistence::languages::{self, LANGUAGES};
+use crate::persistence::models::{CompilerConfig, SyntaxConfig, SyntaxProblem};
+
+// Configuración por defecto compilada dentro del binario
+pub const DEFAULT_COMPILER_CONFIG: &str = include_str!("../config/compiler.toml");
+pub const DEFAULT_SYNTAX_CONFIG: &str = include_str!("../config/syntax.toml");
+
+// Archivo de proyecto que se busca desde el directorio del programa hacia arriba
+pub const PROJECT_FILE: &str = "fabric.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    Invalid {
+        path: PathBuf,
+        problems: Vec<SyntaxProblem>,
+    },
+    UnknownLanguage(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "No se pudo leer {}: {}", path.display(), source)
+            }
+            ConfigError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            ConfigError::Invalid { path, problems } => {
+                write!(f, "{}: sintaxis no válida", path.display())?;
+                for problem in problems {
+                    write!(f, "\n  - {}", problem)?;
+                }
+                Ok(())
+            }
+            ConfigError::UnknownLanguage(name) => {
+                let names: Vec<&str> = LANGUAGES.iter().map(|pack| pack.name).collect();
+                write!(
+                    f,
+                    "Idioma desconocido: {} (disponibles: {})",
+                    name,
+                    names.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub fn read_config_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, ConfigError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse_config(&text, path)
+}
+
+// `path` solo se usa para informar errores
+pub fn parse_config<T: DeserializeOwned>(text: &str, path: &Path) -> Result<T, ConfigError> {
+    toml::from_str(text).map_err(|e| {
+        let offset = e.span().map_or(0, |span| span.start);
+        let (line, column) = line_column(text, offset);
+        ConfigError::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: e.message().to_string(),
+        }
+    })
+}
+
+pub fn read_syntax_file(path: impl AsRef<Path>) -> Result<SyntaxConfig, ConfigError> {
+    let path = path.as_ref();
+    let syntax = read_config_file(path)?;
+    validate_syntax(syntax, path)
+}
+
+// La sintaxis incluida, leída una sola vez
+pub fn default_syntax() -> &'static SyntaxConfig {
+    static SYNTAX: LazyLock<SyntaxConfig> = LazyLock::new(|| {
+        parse_config(DEFAULT_SYNTAX_CONFIG, Path::new("syntax.toml"))
+            .expect("La sintaxis incluida no es válida")
+    });
+    &SYNTAX
+}
+
+pub(crate) fn validate_syntax(
+    syntax: SyntaxConfig,
+    path: &Path,
+) -> Result<SyntaxConfig, ConfigError> {
+    match syntax.validate() {
+        Ok(()) => Ok(syntax),
+        Err(problems) => Err(ConfigError::Invalid {
+            path: path.to_path_buf(),
+            problems,
+        }),
+    }
+}
+
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (line, column)
+}
+
+// Las secciones de `fabric.toml` tienen el mismo formato que los archivos por separado
+#[derive(Deserialize)]
+struct ProjectFile {
+    compiler: Option<CompilerConfig>,
+    syntax: Option<SyntaxConfig>,
+}
+
+// Opciones de la línea de comandos (`--config`, `--syntax` y `--lang`)
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub compiler: Option<PathBuf>,
+    pub syntax: Option<PathBuf>,
+    pub lang: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Configs {
+    pub compiler: CompilerConfig,
+    pub syntax: SyntaxConfig,
+    pub messages: MessageCatalog,
+}
+
+// Orden de búsqueda: opción de la línea de comandos, `fabric.toml` en `start_dir`
+// o sus ancestros, directorio de configuración del usuario y por último la
+// configuración incluida en el binario. Un idioma elegido con `--lang` se
+// antepone a `fabric.toml`; el de `lang` en compiler.toml, al directorio del usuario
+pub fn load_configs(overrides: &ConfigOverrides, start_dir: &Path) -> Result<Configs, ConfigError> {
+    let (project_compiler, project_syntax) = match find_project_file(start_dir) {
+        Some(path) => {
+            let project: ProjectFile = read_config_file(&path)?;
+            let syntax = match project.syntax {
+                Some(syntax) => Some(validate_syntax(syntax, &path)?),
+                None => None,
+            };
+            (project.compiler, syntax)
+        }
+        None => (None, None),
+    };
+
+    let compiler: CompilerConfig = match (&overrides.compiler, project_compiler) {
+        (Some(path), _) => read_config_file(path)?,
+        (None, Some(config)) => config,
+        (None, None) => user_or_default("compiler.toml", DEFAULT_COMPILER_CONFIG)?.0,
+    };
+    let language = match overrides.lang.as_ref().or(compiler.lang.as_ref()) {
+        Some(name) => Some(languages::find_language(name)?),
+        None => None,
+    };
+    let syntax = if let Some(path) = &overrides.syntax {
+        read_syntax_file(path)?
+    } else if let (Some(_), Some(pack)) = (&overrides.lang, language) {
+        pack.syntax()?
+    } else if let Some(config) = project_syntax {
+        config
+    } else if let Some(pack) = language {
+        pack.syntax()?
+    } else {
+        let (syntax, path) = user_or_default("syntax.toml", DEFAULT_SYNTAX_CONFIG)?;
+        validate_syntax(syntax, &path)?
+    };
+    let messages = match language {
+        Some(pack) => pack.messages()?,
+        None => MessageCatalog::default(),
+    };
+    Ok(Configs {
+        compiler,
+        syntax,
+        messages,
+    })
+}
+
+pub fn find_project_file(start_dir: &Path) -> Option<PathBuf> {
+    let start = fs::canonicalize(start_dir).unwrap_or_else(|_| start_dir.to_path_buf());
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+// Devuelve también la ruta de origen para informar errores posteriores
+fn user_or_default<T: DeserializeOwned>(
+    file_name: &str,
+    default: &str,
+) -> Result<(T, PathBuf), ConfigError> {
+    match user_config_dir().map(|dir| dir.join(file_name)) {
+        Some(path) if path.is_file() => Ok((read_config_file(&path)?, path)),
+        _ => Ok((
+            parse_config(default, Path::new(file_name))?,
+            PathBuf::from(file_name),
+        )),
+    }
+}
+
+// $XDG_CONFIG_HOME/fabriclang, %APPDATA%\fabriclang o ~/.config/fabriclang
+pub fn user_config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("fabriclang"))
+}
@@ -1,4 +1,10 @@
-use::serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ::serde::Deserialize;
+
+use crate::core::lexer;
+use crate::core::token::Token;
 
 // Compiler Config
 #[derive(Debug, Deserialize)]
@@ -7,18 +13,193 @@ pub struct CompilerConfig {
     pub executable_name: String,
     pub extension: String,
     pub version: String,
+    #[serde(default = "default_max_call_depth")]
+    pub max_call_depth: usize,
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+fn default_max_call_depth() -> usize {
+    crate::core::interpreter::DEFAULT_MAX_CALL_DEPTH
 }
 
 // Syntax Config
-#[derive(Debug, Deserialize)]
+fn default_break() -> String {
+    "break".to_string()
+}
+
+fn default_continue() -> String {
+    "continue".to_string()
+}
+
+// Las claves desconocidas se guardan al leer el archivo y `validate` las informa
+// junto con los demás problemas
+type UnknownKeys = BTreeMap<String, toml::Value>;
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct SyntaxConfig {
     pub types: Types,
     pub keywords: Keywords,
     pub literals: Literals,
     pub functions: Functions,
+    #[serde(flatten)]
+    pub unknown: UnknownKeys,
 }
 
-#[derive(Debug, Deserialize)]
+// Problema en la definición de un dialecto
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxProblem {
+    UnknownKey {
+        key: String,
+    },
+    Empty {
+        key: &'static str,
+    },
+    InvalidIdentifier {
+        key: &'static str,
+        word: String,
+    },
+    Duplicate {
+        word: String,
+        keys: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for SyntaxProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxProblem::UnknownKey { key } => write!(f, "{} no es una clave conocida", key),
+            SyntaxProblem::Empty { key } => write!(f, "{} está vacío", key),
+            SyntaxProblem::InvalidIdentifier { key, word } => {
+                write!(f, "{} = \"{}\" no es un identificador válido", key, word)
+            }
+            SyntaxProblem::Duplicate { word, keys } => {
+                write!(f, "\"{}\" se usa en {}", word, keys.join(", "))
+            }
+        }
+    }
+}
+
+impl SyntaxConfig {
+    // Cada palabra del dialecto junto a su clave en el archivo
+    pub fn entries(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("types.IntType", &self.types.int_type),
+            ("types.BigIntType", &self.types.big_int_type),
+            ("types.FloatType", &self.types.float_type),
+            ("types.DoubleType", &self.types.double_type),
+            ("types.StringType", &self.types.string_type),
+            ("types.BoolType", &self.types.bool_type),
+            ("types.CharType", &self.types.char_type),
+            ("types.VoidType", &self.types.void_type),
+            ("keywords.If", &self.keywords.r#if),
+            ("keywords.Else", &self.keywords.r#else),
+            ("keywords.While", &self.keywords.r#while),
+            ("keywords.For", &self.keywords.r#for),
+            ("keywords.Switch", &self.keywords.switch),
+            ("keywords.Case", &self.keywords.case),
+            ("keywords.Default", &self.keywords.default),
+            ("keywords.Break", &self.keywords.r#break),
+            ("keywords.Continue", &self.keywords.r#continue),
+            ("keywords.Function", &self.keywords.function),
+            ("keywords.Return", &self.keywords.r#return),
+            ("literals.True", &self.literals.r#true),
+            ("literals.False", &self.literals.r#false),
+            ("functions.Print", &self.functions.print),
+            ("functions.Println", &self.functions.println),
+            ("functions.Input", &self.functions.input),
+        ]
+    }
+
+    // Palabra del dialecto para un tipo, palabra reservada, literal o función incluida
+    pub fn word(&self, token: &Token) -> Option<&str> {
+        let word = match token {
+            Token::IntType => &self.types.int_type,
+            Token::BigIntType => &self.types.big_int_type,
+            Token::FloatType => &self.types.float_type,
+            Token::DoubleType => &self.types.double_type,
+            Token::StringType => &self.types.string_type,
+            Token::BoolType => &self.types.bool_type,
+            Token::CharType => &self.types.char_type,
+            Token::VoidType => &self.types.void_type,
+            Token::If => &self.keywords.r#if,
+            Token::Else => &self.keywords.r#else,
+            Token::While => &self.keywords.r#while,
+            Token::For => &self.keywords.r#for,
+            Token::Switch => &self.keywords.switch,
+            Token::Case => &self.keywords.case,
+            Token::Default => &self.keywords.default,
+            Token::Break => &self.keywords.r#break,
+            Token::Continue => &self.keywords.r#continue,
+            Token::Function => &self.keywords.function,
+            Token::Return => &self.keywords.r#return,
+            Token::BoolLiteral(true) => &self.literals.r#true,
+            Token::BoolLiteral(false) => &self.literals.r#false,
+            Token::Print => &self.functions.print,
+            Token::Println => &self.functions.println,
+            Token::Input => &self.functions.input,
+            _ => return None,
+        };
+        Some(word)
+    }
+
+    // Secciones y claves del archivo que no corresponden a ninguna palabra
+    fn unknown_keys(&self) -> Vec<String> {
+        let sections = [
+            ("types", &self.types.unknown),
+            ("keywords", &self.keywords.unknown),
+            ("literals", &self.literals.unknown),
+            ("functions", &self.functions.unknown),
+        ];
+        let mut keys: Vec<String> = self.unknown.keys().cloned().collect();
+        for (section, unknown) in sections {
+            keys.extend(unknown.keys().map(|key| format!("{}.{}", section, key)));
+        }
+        keys
+    }
+
+    // El lexer solo reconoce palabras con forma de identificador, y una palabra
+    // repetida dejaría a una de sus claves sin uso
+    pub fn validate(&self) -> Result<(), Vec<SyntaxProblem>> {
+        let entries = self.entries();
+        let mut problems: Vec<SyntaxProblem> = self
+            .unknown_keys()
+            .into_iter()
+            .map(|key| SyntaxProblem::UnknownKey { key })
+            .collect();
+        for &(key, word) in &entries {
+            if word.is_empty() {
+                problems.push(SyntaxProblem::Empty { key });
+            } else if !lexer::is_identifier(word) {
+                problems.push(SyntaxProblem::InvalidIdentifier {
+                    key,
+                    word: word.to_string(),
+                });
+            }
+        }
+        for (i, &(_, word)) in entries.iter().enumerate() {
+            let first = entries[..i].iter().all(|&(_, other)| other != word);
+            let keys: Vec<&'static str> = entries[i..]
+                .iter()
+                .filter(|&&(_, other)| other == word)
+                .map(|&(key, _)| key)
+                .collect();
+            if first && !word.is_empty() && keys.len() > 1 {
+                problems.push(SyntaxProblem::Duplicate {
+                    word: word.to_string(),
+                    keys,
+                });
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Types {
     #[serde(rename = "IntType")]
     pub int_type: String,
@@ -36,9 +217,11 @@ pub struct Types {
     pub char_type: String,
     #[serde(rename = "VoidType")]
     pub void_type: String,
+    #[serde(flatten)]
+    pub unknown: UnknownKeys,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Keywords {
     #[serde(rename = "If")]
     pub r#if: String,
@@ -54,21 +237,30 @@ pub struct Keywords {
     pub case: String,
     #[serde(rename = "Default")]
     pub default: String,
+    // Añadidas después: los dialectos anteriores pueden no tenerlas
+    #[serde(rename = "Break", default = "default_break")]
+    pub r#break: String,
+    #[serde(rename = "Continue", default = "default_continue")]
+    pub r#continue: String,
     #[serde(rename = "Function")]
     pub function: String,
     #[serde(rename = "Return")]
     pub r#return: String,
+    #[serde(flatten)]
+    pub unknown: UnknownKeys,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Literals {
     #[serde(rename = "True")]
     pub r#true: String,
     #[serde(rename = "False")]
     pub r#false: String,
+    #[serde(flatten)]
+    pub unknown: UnknownKeys,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Functions {
     #[serde(rename = "Print")]
     pub print: String,
@@ -76,4 +268,6 @@ pub struct Functions {
     pub println: String,
     #[serde(rename = "Input")]
     pub input: String,
-}
\ No newline at end of file
+    #[serde(flatten)]
+    pub unknown: UnknownKeys,
+}
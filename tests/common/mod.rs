@@ -0,0 +1,69 @@
+// Utilidades compartidas por las pruebas, tanto las que usan la biblioteca
+// como las que ejecutan el binario; cada prueba usa solo una parte
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use fabric_lang::core::ast::Stmt;
+use fabric_lang::core::interpreter::{Interpreter, RuntimeError};
+use fabric_lang::core::lexer::Lexer;
+use fabric_lang::core::parser::Parser;
+use fabric_lang::persistence::loader::{DEFAULT_SYNTAX_CONFIG, parse_config};
+use fabric_lang::persistence::models::SyntaxConfig;
+
+// La sintaxis incluida en el binario, sin depender del directorio actual
+pub fn syntax() -> SyntaxConfig {
+    parse_config(DEFAULT_SYNTAX_CONFIG, Path::new("syntax.toml")).unwrap()
+}
+
+pub fn parse(source: &str) -> Vec<Stmt> {
+    let (tokens, errors) = Lexer::new(source, &syntax()).tokenize();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (ast, errors) = Parser::new(tokens).parse();
+    assert!(errors.is_empty(), "{:?}", errors);
+    ast
+}
+
+pub fn run(interpreter: &mut Interpreter, source: &str) -> Result<(), RuntimeError> {
+    interpreter.interpret(parse(source))
+}
+
+// El binario compilado con sus argumentos; cada prueba añade directorio o entorno
+pub fn fabriclang(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_fabriclang"));
+    command.args(args);
+    command
+}
+
+// Ejecuta el comando con `input` como entrada y devuelve salida, errores y código
+pub fn output(command: &mut Command, input: &str) -> (String, String, Option<i32>) {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("No se pudo ejecutar fabriclang");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        String::from_utf8(output.stdout).expect("Salida no es UTF-8"),
+        String::from_utf8(output.stderr).expect("Salida no es UTF-8"),
+        output.status.code(),
+    )
+}
+
+// Directorio temporal vacío, propio de cada prueba y de cada ejecución
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fabriclang-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::Path;
+
+use fabric_lang::persistence::loader::{
+    ConfigError, DEFAULT_SYNTAX_CONFIG, parse_config, read_config_file,
+};
+use fabric_lang::persistence::models::{SyntaxConfig, SyntaxProblem};
+
+mod common;
+
+use common::{fabriclang, output, scratch_dir};
+
+// Sintaxis por defecto con `println` renombrado
+fn renamed_println(name: &str) -> String {
+    DEFAULT_SYNTAX_CONFIG.replace("Println = \"println\"", &format!("Println = \"{}\"", name))
+}
+
+// Se ejecuta fuera del repositorio y con un directorio de usuario propio
+fn run(dir: &Path, user_config: &Path, args: &[&str]) -> (String, String, Option<i32>) {
+    output(
+        fabriclang(args)
+            .current_dir(dir)
+            .env("XDG_CONFIG_HOME", user_config),
+        "",
+    )
+}
+
+#[test]
+fn embedded_defaults_work_from_any_directory() {
+    let dir = scratch_dir("config-defaults");
+    fs::write(dir.join("hola.fbr"), "println(\"hola\");").unwrap();
+
+    let (stdout, stderr, code) = run(&dir, &dir.join("usuario"), &["hola.fbr"]);
+    assert_eq!((stdout.as_str(), code), ("hola\n", Some(0)), "{}", stderr);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn config_sources_are_searched_in_order() {
+    let dir = scratch_dir("config-order");
+    let project = dir.join("proyecto");
+    let scripts = project.join("src");
+    let user = dir.join("usuario");
+    fs::create_dir_all(&scripts).unwrap();
+    fs::create_dir_all(user.join("fabriclang")).unwrap();
+
+    let program = scripts.join("programa.fbr");
+    let program = program.to_str().unwrap();
+    let run_with = |source: &str, args: &[&str]| {
+        fs::write(scripts.join("programa.fbr"), source).unwrap();
+        run(&dir, &user, args)
+    };
+
+    // Directorio del usuario
+    fs::write(
+        user.join("fabriclang/syntax.toml"),
+        renamed_println("decir"),
+    )
+    .unwrap();
+    assert_eq!(run_with("decir(1);", &[program]).0, "1\n");
+
+    // fabric.toml en un ancestro del programa
+    let project_syntax = renamed_println("mostrar").replace("\n[", "\n[syntax.");
+    fs::write(
+        project.join("fabric.toml"),
+        format!("[syntax.{}", &project_syntax[1..]),
+    )
+    .unwrap();
+    assert_eq!(run_with("mostrar(2);", &[program]).0, "2\n");
+
+    // Opción de la línea de comandos
+    let flag_syntax = dir.join("otra.toml");
+    fs::write(&flag_syntax, renamed_println("escribir")).unwrap();
+    let flag_syntax = flag_syntax.to_str().unwrap();
+    assert_eq!(
+        run_with("escribir(3);", &["--syntax", flag_syntax, program]).0,
+        "3\n"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn invalid_config_reports_path_line_and_column() {
+    let dir = scratch_dir("config-invalid");
+    let path = dir.join("syntax.toml");
+    fs::write(&path, "[types]\nIntType = \"int\"\nStringType = 3\n").unwrap();
+
+    match read_config_file::<SyntaxConfig>(&path) {
+        Err(ConfigError::Parse {
+            path: reported,
+            line,
+            column,
+            ..
+        }) => {
+            assert_eq!(reported, path);
+            assert_eq!((line, column), (3, 14));
+        }
+        other => panic!("error inesperado: {:?}", other.map(|_| ())),
+    }
+    assert!(matches!(
+        read_config_file::<SyntaxConfig>(dir.join("no_existe.toml")),
+        Err(ConfigError::Io { .. })
+    ));
+
+    fs::write(dir.join("fabric.toml"), "[compiler\n").unwrap();
+    fs::write(dir.join("hola.fbr"), "println(1);").unwrap();
+    let (_, stderr, code) = run(&dir, &dir.join("usuario"), &["hola.fbr"]);
+    assert_eq!(code, Some(78));
+    assert!(stderr.starts_with("Error de configuración: "), "{}", stderr);
+    assert!(stderr.contains("fabric.toml:1:10: "), "{}", stderr);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn default_syntax() -> SyntaxConfig {
+    parse_config(DEFAULT_SYNTAX_CONFIG, Path::new("syntax.toml")).unwrap()
+}
+
+#[test]
+fn validation_reports_every_problem_in_a_dialect() {
+    assert_eq!(default_syntax().validate(), Ok(()));
+
+    let mut syntax = default_syntax();
+    syntax.keywords.r#while = "if".to_string();
+    syntax.types.string_type = String::new();
+    syntax.functions.print = "2print".to_string();
+    syntax.literals.r#false = "no es".to_string();
+    assert_eq!(
+        syntax.validate(),
+        Err(vec![
+            SyntaxProblem::Empty {
+                key: "types.StringType"
+            },
+            SyntaxProblem::InvalidIdentifier {
+                key: "literals.False",
+                word: "no es".to_string(),
+            },
+            SyntaxProblem::InvalidIdentifier {
+                key: "functions.Print",
+                word: "2print".to_string(),
+            },
+            SyntaxProblem::Duplicate {
+                word: "if".to_string(),
+                keys: vec!["keywords.If", "keywords.While"],
+            },
+        ])
+    );
+
+    let unknown = DEFAULT_SYNTAX_CONFIG.replace("[literals]\n", "[literals]\nMaybe = \"quizas\"\n")
+        + "\n[colores]\nRojo = \"rojo\"\n";
+    let syntax: SyntaxConfig = parse_config(&unknown, Path::new("syntax.toml")).unwrap();
+    assert_eq!(
+        syntax.validate(),
+        Err(vec![
+            SyntaxProblem::UnknownKey {
+                key: "colores".to_string()
+            },
+            SyntaxProblem::UnknownKey {
+                key: "literals.Maybe".to_string()
+            },
+        ])
+    );
+}
+
+#[test]
+fn dialects_without_break_and_continue_still_load() {
+    let old = DEFAULT_SYNTAX_CONFIG
+        .replace("Break = \"break\"\n", "")
+        .replace("Continue = \"continue\"\n", "");
+    assert!(!old.contains("Break"));
+
+    let syntax: SyntaxConfig = parse_config(&old, Path::new("syntax.toml")).unwrap();
+    assert_eq!(syntax.keywords.r#break, "break");
+    assert_eq!(syntax.keywords.r#continue, "continue");
+    assert_eq!(syntax.validate(), Ok(()));
+}
+
+#[test]
+fn config_check_validates_dialect_files() {
+    let dir = scratch_dir("config-check");
+    let user = dir.join("usuario");
+    fs::write(dir.join("bueno.toml"), DEFAULT_SYNTAX_CONFIG).unwrap();
+    fs::write(
+        dir.join("malo.toml"),
+        DEFAULT_SYNTAX_CONFIG.replace("Else = \"else\"", "Else = \"if\""),
+    )
+    .unwrap();
+
+    let (stdout, _, code) = run(&dir, &user, &["config", "check", "bueno.toml"]);
+    assert_eq!((stdout.as_str(), code), ("bueno.toml: correcto\n", Some(0)));
+    let (stdout, _, code) = run(&dir, &user, &["config", "check"]);
+    assert_eq!(
+        (stdout.as_str(), code),
+        ("Configuración correcta\n", Some(0))
+    );
+
+    let (_, stderr, code) = run(&dir, &user, &["config", "check", "malo.toml"]);
+    assert_eq!(code, Some(78));
+    assert_eq!(
+        stderr,
+        "Error de configuración: malo.toml: sintaxis no válida\n  - \"if\" se usa en keywords.If, keywords.Else\n"
+    );
+
+    fs::write(
+        dir.join("raro.toml"),
+        DEFAULT_SYNTAX_CONFIG.replace("[keywords]\n", "[keywords]\nIff = \"si\"\n"),
+    )
+    .unwrap();
+    let (_, stderr, code) = run(&dir, &user, &["config", "check", "raro.toml"]);
+    assert_eq!(code, Some(78));
+    assert_eq!(
+        stderr,
+        "Error de configuración: raro.toml: sintaxis no válida\n  - keywords.Iff no es una clave conocida\n"
+    );
+
+    // La misma validación se aplica antes de ejecutar un programa
+    fs::write(dir.join("hola.fbr"), "println(1);").unwrap();
+    let (stdout, stderr, code) = run(&dir, &user, &["--syntax", "malo.toml", "hola.fbr"]);
+    assert_eq!((stdout.as_str(), code), ("", Some(78)));
+    assert!(stderr.contains("sintaxis no válida"), "{}", stderr);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
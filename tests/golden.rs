@@ -0,0 +1,68 @@
+use std::fs;
+
+mod common;
+
+use common::{fabriclang, output, scratch_dir};
+
+// Los programas de tests/ se comprueban con el subcomando `test`, igual que
+// lo haría un usuario, para ambos motores
+fn fabriclang_test(args: &[&str]) -> (String, Option<i32>) {
+    let (stdout, _, code) = output(fabriclang(&["test"]).args(args), "");
+    (stdout, code)
+}
+
+#[test]
+fn programs_match_their_expectation_files() {
+    for flags in [&[][..], &["-b"][..]] {
+        let (stdout, code) = fabriclang_test(&[flags, &["tests"]].concat());
+        assert_eq!(code, Some(0), "{:?}\n{}", flags, stdout);
+        assert!(stdout.contains("test tests/banco.fbr ... ok\n"));
+    }
+}
+
+#[test]
+fn bless_writes_expectations_and_mismatches_fail() {
+    let dir = scratch_dir("bless");
+    let program = dir.join("division.fbr");
+    fs::write(
+        &program,
+        "println(7);\nint x = input();\nprintln(10 / x);\n",
+    )
+    .unwrap();
+    fs::write(dir.join("division.in"), "0\n").unwrap();
+    let dir_arg = dir.to_str().unwrap();
+
+    let (stdout, code) = fabriclang_test(&["--bless", dir_arg]);
+    assert_eq!(code, Some(0), "{}", stdout);
+    assert!(stdout.contains("division.fbr ... actualizado\n"));
+    assert_eq!(fs::read_to_string(dir.join("division.out")).unwrap(), "7\n");
+    assert_eq!(
+        fs::read_to_string(dir.join("division.exit")).unwrap(),
+        "70\n"
+    );
+    assert!(
+        fs::read_to_string(dir.join("division.err"))
+            .unwrap()
+            .starts_with("error[E504]: División por cero\n")
+    );
+
+    let (stdout, code) = fabriclang_test(&[dir_arg]);
+    assert_eq!(code, Some(0), "{}", stdout);
+
+    // Con otra entrada cambian la salida y el código, y ambos se informan
+    fs::write(dir.join("division.in"), "2\n").unwrap();
+    let (stdout, code) = fabriclang_test(&[dir_arg]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("division.fbr ... FALLÓ\n"));
+    assert!(stdout.contains("division.fbr (out) ----\nesperado:\n7\n\nobtenido:\n7\n5\n"));
+    assert!(stdout.contains("division.fbr (exit) ----\nesperado:\n70\nobtenido:\n0\n"));
+    assert!(stdout.ends_with("resultado: FALLÓ. 0 correctos; 1 fallidos\n"));
+
+    // Al volver a actualizar se eliminan las expectativas que ya no aplican
+    let (_, code) = fabriclang_test(&["--bless", dir_arg]);
+    assert_eq!(code, Some(0));
+    assert!(!dir.join("division.err").exists());
+    assert!(!dir.join("division.exit").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,189 @@
+use fabric_lang::core::interpreter::{
+    self, DEFAULT_MAX_CALL_DEPTH, Interpreter, RuntimeErrorKind, Value,
+};
+use fabric_lang::core::io::MemoryIo;
+use fabric_lang::core::token::Token;
+
+mod common;
+
+use common::{parse, syntax};
+
+const PLUGIN: &str = "
+int umbral = 10;
+string function clasificar(int valor) {
+    if (valor > umbral) {
+        return \"alto\";
+    }
+    return \"bajo\";
+}
+void function avisar(string mensaje) {
+    println(mensaje);
+}
+";
+
+// Carga el script como lo haría un servicio que lo usa de plugin
+fn plugin() -> (Interpreter, MemoryIo) {
+    let io = MemoryIo::new("");
+    let mut interpreter = Interpreter::new(syntax());
+    interpreter.set_io(io.clone());
+    interpreter.interpret(parse(PLUGIN)).unwrap();
+    (interpreter, io)
+}
+
+#[test]
+fn host_calls_script_functions_by_name() {
+    let (mut interpreter, io) = plugin();
+
+    assert_eq!(
+        interpreter
+            .call("clasificar", vec![Value::Int(42)])
+            .unwrap(),
+        Value::String("alto".to_string())
+    );
+    assert_eq!(
+        interpreter
+            .call("avisar", vec![Value::String("hola".to_string())])
+            .unwrap(),
+        Value::Void
+    );
+    assert_eq!(io.output(), "hola\n");
+}
+
+#[test]
+fn host_calls_are_checked_like_script_calls() {
+    let (mut interpreter, _) = plugin();
+
+    let error = interpreter.call("desconocida", vec![]).unwrap_err();
+    assert_eq!(
+        *error.kind,
+        RuntimeErrorKind::UndefinedFunction("desconocida".to_string())
+    );
+
+    let error = interpreter.call("clasificar", vec![]).unwrap_err();
+    assert_eq!(error.kind.code(), "E505");
+
+    let error = interpreter
+        .call("clasificar", vec![Value::Bool(true)])
+        .unwrap_err();
+    assert_eq!(
+        *error.kind,
+        RuntimeErrorKind::TypeMismatch {
+            expected: Token::IntType,
+            found: Value::Bool(true),
+        }
+    );
+}
+
+#[test]
+fn host_reads_and_writes_globals() {
+    let (mut interpreter, _) = plugin();
+
+    assert_eq!(interpreter.get_global("umbral"), Some(Value::Int(10)));
+    assert_eq!(interpreter.get_global("valor"), None);
+
+    interpreter.set_global("umbral", Value::Int(100)).unwrap();
+    assert_eq!(
+        interpreter
+            .call("clasificar", vec![Value::Int(42)])
+            .unwrap(),
+        Value::String("bajo".to_string())
+    );
+
+    let error = interpreter
+        .set_global("umbral", Value::String("x".to_string()))
+        .unwrap_err();
+    assert_eq!(error.kind.code(), "E503");
+    let error = interpreter.set_global("otra", Value::Int(1)).unwrap_err();
+    assert_eq!(error.kind.code(), "E501");
+
+    interpreter
+        .define_global("otra", Token::BoolType, Value::Bool(true))
+        .unwrap();
+    assert_eq!(interpreter.get_global("otra"), Some(Value::Bool(true)));
+    assert!(
+        interpreter
+            .define_global("mala", Token::IntType, Value::Void)
+            .is_err()
+    );
+}
+
+#[test]
+fn host_lists_declared_functions_with_signatures() {
+    let (interpreter, _) = plugin();
+    let signatures: Vec<_> = interpreter
+        .functions()
+        .into_iter()
+        .map(|f| (f.name, f.return_type, f.params))
+        .collect();
+
+    assert_eq!(
+        signatures,
+        [
+            (
+                "avisar".to_string(),
+                Token::VoidType,
+                vec![(Token::StringType, "mensaje".to_string())]
+            ),
+            (
+                "clasificar".to_string(),
+                Token::StringType,
+                vec![(Token::IntType, "valor".to_string())]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn runaway_recursion_from_the_host_is_an_error() {
+    let source = "int function bajar(int n) {\n    return bajar(n + 1);\n}\n";
+    interpreter::with_call_stack(DEFAULT_MAX_CALL_DEPTH, || {
+        let mut interpreter = Interpreter::new(syntax());
+        interpreter.interpret(parse(source)).unwrap();
+
+        let error = interpreter.call("bajar", vec![Value::Int(0)]).unwrap_err();
+        assert_eq!(
+            *error.kind,
+            RuntimeErrorKind::StackOverflow {
+                limit: DEFAULT_MAX_CALL_DEPTH
+            }
+        );
+        assert_eq!(error.call_stack.len(), DEFAULT_MAX_CALL_DEPTH);
+        // El intérprete sigue siendo usable después del error
+        let error = interpreter.call("bajar", vec![Value::Int(0)]).unwrap_err();
+        assert_eq!(error.kind.code(), "E509");
+    })
+    .unwrap();
+}
+
+#[test]
+fn deeply_nested_calls_stop_before_exhausting_the_native_stack() {
+    let nested = format!("{}bajar(n + 1){}", "(1 + ".repeat(40), ")".repeat(40));
+    let source = format!("int function bajar(int n) {{\n    return {};\n}}\n", nested);
+    interpreter::with_call_stack(1_000, || {
+        let mut interpreter = Interpreter::new(syntax());
+        interpreter.set_max_call_depth(1_000);
+        interpreter.interpret(parse(&source)).unwrap();
+
+        let error = interpreter.call("bajar", vec![Value::Int(0)]).unwrap_err();
+        let RuntimeErrorKind::NativeStackExhausted { depth } = *error.kind else {
+            panic!("error inesperado: {:?}", error.kind);
+        };
+        // La profundidad informada es la alcanzada, no el límite configurado
+        assert!(depth > 0 && depth < 1_000, "{}", depth);
+        assert_eq!(error.call_stack.len(), depth);
+        assert_eq!(error.kind.code(), "E516");
+    })
+    .unwrap();
+}
+
+#[test]
+fn runtime_errors_display_in_one_language() {
+    let source = "int function dividir(int n) {\n    return 10 / n;\n}\nint x = dividir(0);\n";
+    let error = Interpreter::new(syntax())
+        .interpret(parse(source))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "División por cero (en 2:12)\n  en la función 'dividir', llamada en 4:9"
+    );
+}
@@ -0,0 +1,73 @@
+use std::fs;
+
+use fabric_lang::core::ast::Stmt;
+use fabric_lang::core::compiler::Compiler;
+use fabric_lang::core::interpreter::{Interpreter, RuntimeError, RuntimeErrorKind};
+use fabric_lang::core::io::{Io, MemoryIo};
+use fabric_lang::core::vm::Vm;
+
+mod common;
+
+use common::syntax;
+
+fn parse(file: &str) -> Vec<Stmt> {
+    common::parse(&fs::read_to_string(file).unwrap())
+}
+
+fn interpret(file: &str, input: &str) -> (String, Result<(), RuntimeError>) {
+    let io = MemoryIo::new(input);
+    let mut interpreter = Interpreter::new(syntax());
+    interpreter.set_io(io.clone());
+    let result = interpreter.interpret(parse(file));
+    (io.output(), result)
+}
+
+fn run_vm(file: &str, input: &str) -> (String, Result<(), RuntimeError>) {
+    let io = MemoryIo::new(input);
+    let program = Compiler::new().compile(&parse(file)).unwrap();
+    let mut vm = Vm::new(syntax());
+    vm.set_io(io.clone());
+    let result = vm.run(&program);
+    (io.output(), result)
+}
+
+#[test]
+fn programs_read_and_write_through_the_injected_io() {
+    let input = "1\n2\n300\n3\n";
+    for (output, result) in [
+        interpret("tests/banco.fbr", input),
+        run_vm("tests/banco.fbr", input),
+    ] {
+        assert!(result.is_ok());
+        assert!(output.starts_with("=== BIENVENIDO A FABRIC BANK ===\n"));
+        assert!(output.contains("Su saldo actual es: 1000\n"));
+        assert!(output.contains("Retiro exitoso. Nuevo saldo: 700\n"));
+        assert!(output.ends_with("Gracias por usar Fabric.\n"));
+    }
+}
+
+#[test]
+fn end_of_input_is_a_runtime_error() {
+    for (output, result) in [
+        interpret("tests/banco.fbr", "1\n"),
+        run_vm("tests/banco.fbr", "1\n"),
+    ] {
+        let error = result.unwrap_err();
+        assert_eq!(*error.kind, RuntimeErrorKind::EndOfInput);
+        assert_eq!(error.kind.code(), "E512");
+        assert_eq!(error.span.start.line, 54);
+        assert!(output.ends_with("Seleccione una opcion: "));
+    }
+}
+
+#[test]
+fn memory_io_splits_input_into_lines() {
+    let mut io = MemoryIo::new("uno\ndos");
+    assert_eq!(io.read_line().unwrap(), Some("uno\n".to_string()));
+    assert_eq!(io.read_line().unwrap(), Some("dos".to_string()));
+    assert_eq!(io.read_line().unwrap(), None);
+
+    io.write("hola").unwrap();
+    io.flush().unwrap();
+    assert_eq!(io.output(), "hola");
+}
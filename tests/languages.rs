@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::Path;
+
+use fabric_lang::core::diagnostics::Diagnostic;
+use fabric_lang::core::messages::{Message, MessageCatalog, MessageId};
+use fabric_lang::core::token::Token;
+use fabric_lang::persistence::languages::{LANGUAGES, find_language};
+use fabric_lang::persistence::loader::{ConfigError, default_syntax, parse_config};
+
+mod common;
+
+use common::{fabriclang, output, scratch_dir};
+
+// Se ejecuta en `dir`, con su propio directorio de usuario y sin colores
+fn run_in(dir: &Path, args: &[&str]) -> (String, String, Option<i32>) {
+    output(
+        fabriclang(args)
+            .current_dir(dir)
+            .env("NO_COLOR", "1")
+            .env("XDG_CONFIG_HOME", dir.join("usuario")),
+        "",
+    )
+}
+
+fn first_error(stderr: &str) -> &str {
+    stderr.lines().next().unwrap_or_default()
+}
+
+#[test]
+fn every_language_pack_loads() {
+    for pack in LANGUAGES {
+        let syntax = pack.syntax().unwrap();
+        assert_eq!(syntax.validate(), Ok(()), "{}", pack.name);
+        assert!(!pack.messages().unwrap().is_empty(), "{}", pack.name);
+    }
+    assert!(matches!(
+        find_language("xx"),
+        Err(ConfigError::UnknownLanguage(name)) if name == "xx"
+    ));
+}
+
+#[test]
+fn catalogs_fill_in_values_by_message_id() {
+    let catalog: MessageCatalog = parse_config(
+        r#"
+[common]
+UndefinedVariable = "Undefined variable: {}"
+ArityMismatch = "Function '{}' expects {} arguments but got {}"
+
+[runtime]
+StepLimit = "Execution limit reached: more than {} execution steps"
+"#,
+        Path::new("messages.toml"),
+    )
+    .unwrap();
+
+    let message = |id: MessageId, args: &[&str]| {
+        Message::new(id, args.iter().map(|arg| arg.to_string()).collect())
+    };
+    assert_eq!(
+        catalog.translate(
+            &message(MessageId::UndefinedVariable, &["x"]),
+            default_syntax()
+        ),
+        "Undefined variable: x"
+    );
+    assert_eq!(
+        catalog.translate(&message(MessageId::StepLimit, &["10"]), default_syntax()),
+        "Execution limit reached: more than 10 execution steps"
+    );
+    assert_eq!(
+        catalog.translate(
+            &message(MessageId::ArityMismatch, &["f", "2", "3"]),
+            default_syntax()
+        ),
+        "Function 'f' expects 2 arguments but got 3"
+    );
+    // Sin traducción se usa el texto original
+    assert_eq!(
+        catalog.translate(&MessageId::DivisionByZero.into(), default_syntax()),
+        "División por cero"
+    );
+
+    let diagnostic = Diagnostic::new("E501", message(MessageId::UndefinedVariable, &["y"]), 1, 1);
+    assert_eq!(
+        diagnostic.render("x.fbr", "", &catalog, default_syntax(), false),
+        "error[E501]: Undefined variable: y\n --> x.fbr:1:1\n"
+    );
+    assert_eq!(
+        diagnostic.render(
+            "x.fbr",
+            "",
+            &MessageCatalog::default(),
+            default_syntax(),
+            false
+        ),
+        "error[E501]: Variable no definida: y\n --> x.fbr:1:1\n"
+    );
+}
+
+#[test]
+fn keywords_and_types_use_the_active_syntax() {
+    let pack = find_language("es").unwrap();
+    let (catalog, syntax) = (pack.messages().unwrap(), pack.syntax().unwrap());
+    let condition = Message::new(
+        MessageId::ConditionNotBool,
+        vec![Token::BoolType, Token::IntType],
+    );
+    assert_eq!(
+        catalog.translate(&condition, &syntax),
+        "La condición debe ser booleano, se obtuvo entero"
+    );
+    let case = Message::new(
+        MessageId::ExpectedCase,
+        vec![Token::Case, Token::Default, Token::Switch],
+    );
+    assert_eq!(
+        catalog.translate(&case, &syntax),
+        "Se esperaba 'caso' u 'otro' dentro del 'según'"
+    );
+    assert_eq!(
+        catalog.translate(&case, default_syntax()),
+        "Se esperaba 'case' u 'default' dentro del 'switch'"
+    );
+    assert_eq!(
+        condition.to_string(),
+        "La condición debe ser bool, se obtuvo int"
+    );
+}
+
+#[test]
+fn catalogs_reject_unknown_messages_and_missing_values() {
+    let parse = |text: &str| match parse_config::<MessageCatalog>(text, Path::new("messages.toml"))
+    {
+        Err(ConfigError::Parse { message, .. }) => message,
+        other => panic!("resultado inesperado: {:?}", other.map(|_| ())),
+    };
+    let message = parse("[runtime]\nUndefinedVariabel = \"Undefined variable: {}\"\n");
+    assert!(
+        message.contains("Mensaje desconocido: runtime.UndefinedVariabel"),
+        "{}",
+        message
+    );
+    let message = parse("[common]\nUndefinedVariable = \"Undefined variable\"\n");
+    assert!(
+        message.contains("common.UndefinedVariable debe tener 1 valores"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn every_message_is_translated_in_every_pack() {
+    for pack in LANGUAGES {
+        let catalog = pack.messages().unwrap();
+        let missing: Vec<_> = MessageId::ALL
+            .iter()
+            .filter(|id| catalog.get(**id).is_none())
+            .map(|id| format!("{}.{}", id.section(), id.name()))
+            .collect();
+        assert!(missing.is_empty(), "{}: faltan {:?}", pack.name, missing);
+    }
+}
+
+#[test]
+fn lang_selects_syntax_and_messages() {
+    let dir = scratch_dir("lang");
+    let tests = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let fixture = |name: &str| tests.join(name).to_str().unwrap().to_string();
+
+    let (_, stderr, code) = run_in(&dir, &["--lang", "en", &fixture("syntax_errors.fbr")]);
+    assert_eq!(code, Some(65));
+    assert_eq!(
+        first_error(&stderr),
+        "error[E201]: Expected ';' at the end of the declaration"
+    );
+    assert!(
+        stderr.contains("= help: add ';' at the end of the previous statement\n"),
+        "{}",
+        stderr
+    );
+
+    // El mismo programa en portugués, con sus mensajes de ejecución
+    let (program, _, code) = run_in(
+        &dir,
+        &[
+            "translate",
+            "--from",
+            "en",
+            "--to",
+            "pt",
+            &fixture("division_by_zero.fbr"),
+        ],
+    );
+    assert_eq!(code, Some(0));
+    fs::write(dir.join("divisao.fbr"), program).unwrap();
+    let (stdout, stderr, code) = run_in(&dir, &["--lang", "pt", "divisao.fbr"]);
+    assert_eq!((stdout.as_str(), code), ("3\n2\n", Some(70)));
+    assert_eq!(first_error(&stderr), "error[E504]: Divisão por zero");
+    assert!(
+        stderr.contains("= note: na função 'dividir', chamada em 7:10"),
+        "{}",
+        stderr
+    );
+
+    // Los errores de `translate` también se muestran en el idioma elegido
+    fs::write(dir.join("reservada.fbr"), "int si = 1;\n").unwrap();
+    let (_, stderr, code) = run_in(
+        &dir,
+        &[
+            "--lang",
+            "en",
+            "translate",
+            "--from",
+            "en",
+            "--to",
+            "es",
+            "reservada.fbr",
+        ],
+    );
+    assert_eq!(code, Some(65));
+    assert_eq!(
+        first_error(&stderr),
+        "error[E601]: Identifier 'si' is a reserved word (keywords.If) in the target dialect"
+    );
+
+    // `lang` en la configuración del compilador del proyecto
+    fs::write(
+        dir.join("fabric.toml"),
+        "[compiler]\nname = \"FabricLang\"\nexecutable_name = \"fabriclang\"\nextension = \"fbr\"\nversion = \"0.1.0\"\nlang = \"pt\"\n",
+    )
+    .unwrap();
+    let (stdout, _, code) = run_in(&dir, &["divisao.fbr"]);
+    assert_eq!((stdout.as_str(), code), ("3\n2\n", Some(70)));
+    let (_, stderr, code) = run_in(&dir, &["--lang", "es", "divisao.fbr"]);
+    assert_eq!(code, Some(65));
+    assert!(stderr.starts_with("error[E20"), "{}", stderr);
+
+    let (_, stderr, code) = run_in(&dir, &["--lang", "xx", "divisao.fbr"]);
+    assert_eq!(code, Some(78));
+    assert_eq!(
+        stderr,
+        "Error de configuración: Idioma desconocido: xx (disponibles: en, es, pt)\n"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
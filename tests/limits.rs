@@ -0,0 +1,97 @@
+use std::thread;
+use std::time::Duration;
+
+use fabric_lang::core::interpreter::{
+    ExecutionLimits, Interpreter, Limit, RuntimeError, RuntimeErrorKind,
+};
+
+mod common;
+
+use common::{run, syntax};
+
+fn limited(limits: ExecutionLimits) -> Interpreter {
+    let mut interpreter = Interpreter::new(syntax());
+    interpreter.set_limits(limits);
+    interpreter
+}
+
+fn limit_of(result: Result<(), RuntimeError>) -> Limit {
+    match *result.expect_err("debía superar el límite").kind {
+        RuntimeErrorKind::LimitExceeded(limit) => limit,
+        other => panic!("error inesperado: {:?}", other),
+    }
+}
+
+#[test]
+fn step_limit_stops_infinite_loops() {
+    let mut interpreter = limited(ExecutionLimits {
+        max_steps: Some(10_000),
+        ..ExecutionLimits::default()
+    });
+    let result = run(&mut interpreter, "int i = 0; while (true) { i = i + 1; }");
+    assert_eq!(limit_of(result), Limit::Steps(10_000));
+
+    // El presupuesto se renueva en cada ejecución
+    assert!(run(&mut interpreter, "int j = 1;").is_ok());
+}
+
+#[test]
+fn timeout_stops_long_running_scripts() {
+    let mut interpreter = limited(ExecutionLimits {
+        timeout: Some(Duration::from_millis(50)),
+        ..ExecutionLimits::default()
+    });
+    let result = run(&mut interpreter, "while (true) { }");
+    assert_eq!(limit_of(result), Limit::Time(Duration::from_millis(50)));
+}
+
+#[test]
+fn string_length_limit_stops_runaway_growth() {
+    let mut interpreter = limited(ExecutionLimits {
+        max_string_length: Some(1024),
+        ..ExecutionLimits::default()
+    });
+    let result = run(
+        &mut interpreter,
+        "string s = \"ab\"; while (true) { s = s + s; }",
+    );
+    let error = result.unwrap_err();
+    assert_eq!(
+        *error.kind,
+        RuntimeErrorKind::LimitExceeded(Limit::StringLength(1024))
+    );
+    assert_eq!(error.kind.code(), "E510");
+    assert_eq!(error.span.start.column, 37);
+}
+
+#[test]
+fn value_limit_counts_live_variables() {
+    let source = "int function f(int n) { int doble = n * 2; return f(n - 1); } f(10);";
+    let mut interpreter = limited(ExecutionLimits {
+        max_values: Some(50),
+        ..ExecutionLimits::default()
+    });
+    assert_eq!(limit_of(run(&mut interpreter, source)), Limit::Values(50));
+
+    // Las variables de ámbitos ya cerrados no cuentan
+    let source = "int i = 0; while (i < 1000) { int tmp = i; i = tmp + 1; }";
+    let mut interpreter = limited(ExecutionLimits {
+        max_values: Some(5),
+        ..ExecutionLimits::default()
+    });
+    assert!(run(&mut interpreter, source).is_ok());
+}
+
+#[test]
+fn cancel_handle_stops_execution_from_another_thread() {
+    let mut interpreter = limited(ExecutionLimits::default());
+    let handle = interpreter.cancel_handle();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        handle.cancel();
+    });
+
+    let result = run(&mut interpreter, "while (true) { }");
+    canceller.join().unwrap();
+    assert_eq!(*result.unwrap_err().kind, RuntimeErrorKind::Cancelled);
+}
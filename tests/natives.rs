@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use fabric_lang::core::interpreter::{Interpreter, RuntimeErrorKind, Value};
+use fabric_lang::core::io::MemoryIo;
+use fabric_lang::core::token::Token;
+use fabric_lang::core::type_checker::{FunctionSignature, TypeChecker};
+
+mod common;
+
+use common::{parse, run, syntax};
+
+fn signature(params: &[Token], return_type: Token) -> FunctionSignature {
+    FunctionSignature {
+        params: params.to_vec(),
+        return_type,
+    }
+}
+
+// Intérprete con `doble(int) -> int` y `registrar(string) -> void`, que guarda
+// los mensajes en el vector devuelto
+fn host() -> (Interpreter, MemoryIo, Rc<RefCell<Vec<String>>>) {
+    let io = MemoryIo::new("");
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut interpreter = Interpreter::new(syntax());
+    interpreter.set_io(io.clone());
+
+    interpreter.register_native(
+        "doble",
+        signature(&[Token::IntType], Token::IntType),
+        |args| match args {
+            [Value::Int(n)] => Ok(Value::Int(n * 2)),
+            _ => unreachable!(),
+        },
+    );
+    let messages = log.clone();
+    interpreter.register_native(
+        "registrar",
+        signature(&[Token::StringType], Token::VoidType),
+        move |args| {
+            messages.borrow_mut().push(args[0].to_string());
+            Ok(Value::Void)
+        },
+    );
+    (interpreter, io, log)
+}
+
+#[test]
+fn scripts_call_native_functions() {
+    let (mut interpreter, io, log) = host();
+    let source = "int function cuadruple(int n) { return doble(doble(n)); }\n\
+                  println(cuadruple(5));\n\
+                  registrar(\"listo\");";
+    run(&mut interpreter, source).unwrap();
+
+    assert_eq!(io.output(), "20\n");
+    assert_eq!(*log.borrow(), vec!["listo".to_string()]);
+}
+
+#[test]
+fn native_arguments_and_results_are_type_checked() {
+    let (mut interpreter, _, _) = host();
+    let error = run(&mut interpreter, "int x = doble(\"dos\");").unwrap_err();
+    assert_eq!(
+        *error.kind,
+        RuntimeErrorKind::TypeMismatch {
+            expected: Token::IntType,
+            found: Value::String("dos".to_string()),
+        }
+    );
+    assert_eq!(error.span.start.column, 15);
+
+    let error = run(&mut interpreter, "doble(1, 2);").unwrap_err();
+    assert_eq!(error.kind.code(), "E505");
+
+    interpreter.register_native("mentir", signature(&[], Token::BoolType), |_| {
+        Ok(Value::Int(1))
+    });
+    let error = run(&mut interpreter, "bool b = mentir();").unwrap_err();
+    assert_eq!(
+        *error.kind,
+        RuntimeErrorKind::TypeMismatch {
+            expected: Token::BoolType,
+            found: Value::Int(1),
+        }
+    );
+}
+
+#[test]
+fn native_errors_point_at_the_call_site() {
+    let (mut interpreter, _, _) = host();
+    interpreter.register_native(
+        "buscar",
+        signature(&[Token::IntType], Token::StringType),
+        |args| Err(RuntimeErrorKind::Native(format!("No existe el registro {}", args[0])).into()),
+    );
+    let source = "void function cargar() {\n    string s = buscar(7);\n}\ncargar();";
+    let error = run(&mut interpreter, source).unwrap_err();
+
+    assert_eq!(error.kind.to_string(), "No existe el registro 7");
+    assert_eq!(error.kind.code(), "E514");
+    assert_eq!((error.span.start.line, error.span.start.column), (2, 16));
+    let frames: Vec<_> = error
+        .call_stack
+        .iter()
+        .map(|f| f.function.as_str())
+        .collect();
+    assert_eq!(frames, ["buscar", "cargar"]);
+}
+
+#[test]
+fn natives_are_listed_and_can_be_declared_to_the_type_checker() {
+    let (mut interpreter, _, _) = host();
+    run(
+        &mut interpreter,
+        "int function triple(int n) { return n * 3; }",
+    )
+    .unwrap();
+
+    let functions = interpreter.functions();
+    let names: Vec<_> = functions
+        .iter()
+        .map(|f| (f.name.as_str(), f.native))
+        .collect();
+    assert_eq!(
+        names,
+        [("doble", true), ("registrar", true), ("triple", false)]
+    );
+
+    let ast = parse("int x = doble(2);");
+    assert!(TypeChecker::new().check(&ast).is_err());
+    let mut checker = TypeChecker::new();
+    checker.declare_function("doble", signature(&[Token::IntType], Token::IntType));
+    assert!(checker.check(&ast).is_ok());
+}
@@ -0,0 +1,196 @@
+use fabric_lang::core::interpreter::{Interpreter, RuntimeErrorKind, Value};
+use fabric_lang::core::token::Token;
+
+fn samples() -> Vec<Value> {
+    vec![
+        Value::Int(7),
+        Value::BigInt(7),
+        Value::Float(2.5),
+        Value::Double(2.5),
+        Value::String("a".to_string()),
+        Value::Char('c'),
+        Value::Bool(true),
+        Value::Void,
+    ]
+}
+
+fn operators() -> Vec<Token> {
+    vec![
+        Token::Plus,
+        Token::Minus,
+        Token::Multiply,
+        Token::Divide,
+        Token::Modulo,
+        Token::EqualEqual,
+        Token::NotEqual,
+        Token::Greater,
+        Token::Less,
+        Token::GreaterEqual,
+        Token::LessEqual,
+    ]
+}
+
+fn is_numeric(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Int(_) | Value::BigInt(_) | Value::Float(_) | Value::Double(_)
+    )
+}
+
+fn eval(left: Value, op: Token, right: Value) -> Result<Value, RuntimeErrorKind> {
+    Interpreter::execute_binary_op(left, op, right)
+}
+
+#[test]
+fn every_operator_and_type_pair_is_defined_or_a_typed_error() {
+    for left in samples() {
+        for right in samples() {
+            for op in operators() {
+                let result = eval(left.clone(), op.clone(), right.clone());
+                let both_numeric = is_numeric(&left) && is_numeric(&right);
+                let both_strings = matches!((&left, &right), (Value::String(_), Value::String(_)));
+                let defined = both_numeric
+                    || matches!(op, Token::EqualEqual | Token::NotEqual)
+                    || (both_strings && op == Token::Plus);
+
+                match result {
+                    Ok(_) => assert!(defined, "{:?} {:?} {:?} debía fallar", left, op, right),
+                    Err(RuntimeErrorKind::IncompatibleOperands { .. }) => {
+                        assert!(
+                            !defined,
+                            "{:?} {:?} {:?} debía estar definido",
+                            left, op, right
+                        )
+                    }
+                    Err(other) => panic!(
+                        "{:?} {:?} {:?}: error inesperado {:?}",
+                        left, op, right, other
+                    ),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn numeric_operands_are_promoted_to_the_widest_type() {
+    let widest = |left: Value, right: Value| eval(left, Token::Plus, right).unwrap();
+
+    assert_eq!(widest(Value::Int(1), Value::Int(2)), Value::Int(3));
+    assert_eq!(widest(Value::Int(1), Value::BigInt(2)), Value::BigInt(3));
+    assert_eq!(
+        widest(Value::BigInt(1), Value::Float(0.5)),
+        Value::Float(1.5)
+    );
+    assert_eq!(
+        widest(Value::Float(0.5), Value::Double(1.0)),
+        Value::Double(1.5)
+    );
+    assert_eq!(
+        widest(Value::Double(1.0), Value::Int(2)),
+        Value::Double(3.0)
+    );
+    assert_eq!(
+        eval(Value::Int(7), Token::Divide, Value::Double(2.0)),
+        Ok(Value::Double(3.5))
+    );
+    assert_eq!(
+        eval(Value::Int(1), Token::EqualEqual, Value::Double(1.0)),
+        Ok(Value::Bool(true))
+    );
+}
+
+#[test]
+fn comparisons_cover_every_numeric_type() {
+    let cases = [
+        (Token::Greater, false),
+        (Token::Less, true),
+        (Token::GreaterEqual, false),
+        (Token::LessEqual, true),
+        (Token::EqualEqual, false),
+        (Token::NotEqual, true),
+    ];
+    for (op, expected) in cases {
+        assert_eq!(
+            eval(Value::Double(1.5), op.clone(), Value::Double(2.5)),
+            Ok(Value::Bool(expected))
+        );
+        assert_eq!(
+            eval(Value::Int(1), op.clone(), Value::Int(2)),
+            Ok(Value::Bool(expected))
+        );
+        assert_eq!(
+            eval(Value::BigInt(1), op.clone(), Value::Float(2.0)),
+            Ok(Value::Bool(expected))
+        );
+    }
+    assert_eq!(
+        eval(
+            Value::Double(f64::NAN),
+            Token::EqualEqual,
+            Value::Double(f64::NAN)
+        ),
+        Ok(Value::Bool(false))
+    );
+}
+
+#[test]
+fn integer_division_by_zero_is_an_error() {
+    for op in [Token::Divide, Token::Modulo] {
+        assert_eq!(
+            eval(Value::Int(1), op.clone(), Value::Int(0)),
+            Err(RuntimeErrorKind::DivisionByZero)
+        );
+        assert_eq!(
+            eval(Value::BigInt(1), op.clone(), Value::Int(0)),
+            Err(RuntimeErrorKind::DivisionByZero)
+        );
+    }
+    assert_eq!(
+        eval(Value::Double(1.0), Token::Divide, Value::Int(0)),
+        Ok(Value::Double(f64::INFINITY))
+    );
+}
+
+#[test]
+fn integer_overflow_is_an_error() {
+    let cases = [
+        (Value::Int(i32::MAX), Token::Plus, Value::Int(1)),
+        (Value::Int(i32::MIN), Token::Minus, Value::Int(1)),
+        (Value::Int(i32::MAX), Token::Multiply, Value::Int(2)),
+        (Value::Int(i32::MIN), Token::Divide, Value::Int(-1)),
+        (Value::BigInt(i64::MAX), Token::Plus, Value::Int(1)),
+        (Value::BigInt(i64::MIN), Token::Modulo, Value::BigInt(-1)),
+    ];
+    for (left, op, right) in cases {
+        assert_eq!(eval(left, op, right), Err(RuntimeErrorKind::Overflow));
+    }
+    assert_eq!(
+        eval(Value::Int(i32::MAX), Token::Plus, Value::BigInt(1)),
+        Ok(Value::BigInt(i32::MAX as i64 + 1))
+    );
+}
+
+#[test]
+fn mismatched_operands_report_both_values() {
+    assert_eq!(
+        eval(Value::String("a".to_string()), Token::Plus, Value::Int(1)),
+        Err(RuntimeErrorKind::IncompatibleOperands {
+            operator: "+".to_string(),
+            left: Value::String("a".to_string()),
+            right: Value::Int(1),
+        })
+    );
+    assert_eq!(
+        eval(
+            Value::String("a".to_string()),
+            Token::Plus,
+            Value::String("b".to_string())
+        ),
+        Ok(Value::String("ab".to_string()))
+    );
+    assert_eq!(
+        eval(Value::Bool(true), Token::NotEqual, Value::Int(1)),
+        Ok(Value::Bool(true))
+    );
+}
@@ -0,0 +1,467 @@
+use fabric_lang::core::interpreter::Interpreter;
+use fabric_lang::core::lexer::Lexer;
+use fabric_lang::core::messages::MessageId;
+use fabric_lang::core::parser::Parser;
+use fabric_lang::core::token::Token;
+
+mod common;
+
+use common::{fabriclang, output, syntax};
+
+fn run(args: &[&str]) -> (String, String) {
+    run_with_input(args, "")
+}
+
+fn run_with_input(args: &[&str], input: &str) -> (String, String) {
+    let (stdout, stderr, _) = run_with_status(args, input);
+    (stdout, stderr)
+}
+
+fn run_with_status(args: &[&str], input: &str) -> (String, String, Option<i32>) {
+    output(&mut fabriclang(args), input)
+}
+
+#[test]
+fn functions_use_lexical_scope() {
+    let (stdout, stderr) = run(&["-n", "tests/scoping.fbr"]);
+
+    assert_eq!(stdout, "15\n100\n11\n");
+    assert!(stderr.is_empty());
+}
+
+#[test]
+fn functions_cannot_see_caller_locals() {
+    let (stdout, stderr) = run(&["-n", "tests/scoping_lectura.fbr"]);
+    assert!(stdout.is_empty());
+    assert!(stderr.starts_with("error[E501]: Variable no definida: saldo\n"));
+    assert!(stderr.contains(" --> tests/scoping_lectura.fbr:3:12\n"));
+
+    let (stdout, stderr) = run(&["-n", "tests/scoping_escritura.fbr"]);
+    assert!(stdout.is_empty());
+    assert!(stderr.starts_with("error[E501]: Variable no definida: saldo\n"));
+    assert!(stderr.contains(" --> tests/scoping_escritura.fbr:3:5\n"));
+}
+
+#[test]
+fn type_checker_reports_errors_before_running() {
+    let (stdout, stderr) = run(&["tests/type_errors.fbr"]);
+    let headers: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("error") || line.contains("-->"))
+        .collect();
+
+    assert!(stdout.is_empty());
+    assert_eq!(
+        headers,
+        vec![
+            "error[E303]: Error de tipo: se esperaba int pero se recibió string",
+            " --> tests/type_errors.fbr:2:9",
+            "error[E309]: Una función void no puede retornar un valor",
+            " --> tests/type_errors.fbr:5:12",
+            "error[E303]: Error de tipo: se esperaba int pero se recibió bool",
+            " --> tests/type_errors.fbr:9:16",
+            "error[E306]: La condición debe ser bool, se obtuvo int",
+            "  --> tests/type_errors.fbr:13:8",
+            "error[E304]: La función 'doble' espera 1 argumentos pero recibió 2",
+            "  --> tests/type_errors.fbr:16:1",
+            "error[E303]: Error de tipo: se esperaba int pero se recibió string",
+            "  --> tests/type_errors.fbr:17:7",
+            "error[E301]: Variable no definida: y",
+            "  --> tests/type_errors.fbr:18:9",
+        ]
+    );
+}
+
+#[test]
+fn diagnostics_underline_the_offending_range() {
+    let (_, stderr) = run(&["tests/type_errors.fbr"]);
+
+    assert!(stderr.contains(
+        "error[E303]: Error de tipo: se esperaba int pero se recibió string\n \
+         --> tests/type_errors.fbr:2:9\n  \
+         |\n\
+         2 | int x = \"texto\"; // error\n  \
+         |         ^^^^^^^\n"
+    ));
+    assert!(!stderr.contains('\x1b'));
+}
+
+#[test]
+fn vm_matches_interpreter_output() {
+    let programs = [
+        ("tests/fib.fbr", ""),
+        ("tests/switch.fbr", ""),
+        ("tests/logical.fbr", ""),
+        ("tests/for.fbr", ""),
+        ("tests/break_continue.fbr", ""),
+        ("tests/runtime_trace.fbr", ""),
+        ("tests/prueba_tipos.fbr", ""),
+        ("tests/scoping.fbr", ""),
+        ("tests/scoping_lectura.fbr", ""),
+        ("tests/scoping_escritura.fbr", ""),
+        ("tests/ambitos.fbr", ""),
+        ("tests/ambitos_fuera.fbr", ""),
+        ("tests/ambitos_bucle.fbr", ""),
+        ("tests/banco.fbr", "1\n2\n300\n2\n5000\n7\n3\n"),
+    ];
+
+    for (file, input) in programs {
+        let interpreted = run_with_input(&["-n", file], input);
+        let compiled = run_with_input(&["-n", "-b", file], input);
+        assert_eq!(interpreted, compiled, "{}", file);
+    }
+}
+
+#[test]
+fn block_bodies_have_their_own_scope_in_every_pass() {
+    let (stdout, _) = run(&["tests/ambitos.fbr"]);
+    assert_eq!(stdout, "2\n1\n0\n2\n10\n1\n3\n4\n5\n");
+
+    for file in ["tests/ambitos_fuera.fbr", "tests/ambitos_bucle.fbr"] {
+        let (_, stderr, code) = run_with_status(&[file], "");
+        assert_eq!(code, Some(65), "{}", file);
+        assert!(stderr.starts_with("error[E301]: Variable no definida"));
+        for flags in [&["-n"][..], &["-n", "-b"][..]] {
+            let (_, stderr, code) = run_with_status(&[flags, &[file]].concat(), "");
+            assert_eq!(code, Some(70), "{:?} {}", flags, file);
+            assert!(stderr.starts_with("error[E501]: Variable no definida"));
+        }
+    }
+}
+
+#[test]
+fn vm_handles_deep_recursion() {
+    let (stdout, _) = run(&["-b", "tests/recursion_infinita.fbr"]);
+    assert_eq!(stdout, "500");
+}
+
+#[test]
+fn parser_reports_every_syntax_error() {
+    let (stdout, stderr) = run(&["tests/syntax_errors.fbr"]);
+    let locations: Vec<&str> = stderr.lines().filter(|l| l.contains("-->")).collect();
+
+    assert!(stdout.is_empty());
+    assert_eq!(
+        locations,
+        vec![
+            " --> tests/syntax_errors.fbr:2:1",
+            " --> tests/syntax_errors.fbr:3:14",
+            " --> tests/syntax_errors.fbr:6:5",
+            " --> tests/syntax_errors.fbr:8:15",
+        ]
+    );
+}
+
+#[test]
+fn parser_keeps_partial_ast_after_errors() {
+    let (stdout, _) = run(&["-a", "tests/syntax_errors.fbr"]);
+    let statements: Vec<&str> = stdout
+        .lines()
+        .filter(|l| l.starts_with("    ") && !l.starts_with("     "))
+        .map(str::trim)
+        .collect();
+
+    assert_eq!(
+        statements,
+        vec![
+            "Print {",
+            "},",
+            "If {",
+            "},",
+            "VarDeclaration {",
+            "},",
+            "Println {",
+            "},"
+        ]
+    );
+}
+
+#[test]
+fn lexer_reports_every_lexical_error() {
+    let (stdout, stderr) = run(&["tests/lexical_errors.fbr"]);
+    let headers: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("error") || line.contains("-->"))
+        .collect();
+
+    assert!(stdout.is_empty());
+    assert_eq!(
+        headers,
+        vec![
+            "error[E101]: Unexpected character '$'",
+            " --> tests/lexical_errors.fbr:1:11",
+            "error[E101]: Expected '&' after '&'",
+            " --> tests/lexical_errors.fbr:2:16",
+            "error[E103]: Invalid number literal",
+            " --> tests/lexical_errors.fbr:3:9",
+            "error[E103]: Invalid character literal",
+            " --> tests/lexical_errors.fbr:4:10",
+            "error[E103]: Expected digit after decimal point",
+            " --> tests/lexical_errors.fbr:5:11",
+            "error[E102]: Unterminated string literal",
+            " --> tests/lexical_errors.fbr:6:12",
+        ]
+    );
+    assert!(stderr.contains("3 | int n = 12abc;\n  |         ^^^^^\n"));
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    let (stdout, stderr) = run(&["tests/logical.fbr"]);
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    assert!(stderr.is_empty());
+    assert_eq!(
+        lines,
+        vec![
+            "true",
+            "false",
+            "true",
+            "false",
+            "false",
+            "0",
+            "true",
+            "0",
+            "true",
+            "1",
+            "false",
+            "2",
+            "dentro",
+            "precedencia",
+        ]
+    );
+}
+
+#[test]
+fn logical_operators_require_bool_operands() {
+    let (stdout, stderr) = run(&["tests/logical_errors.fbr"]);
+    let headers: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("error") || line.contains("-->"))
+        .collect();
+
+    assert!(stdout.is_empty());
+    assert_eq!(
+        headers,
+        vec![
+            "error[E305]: El operador '&&' requiere operandos bool, se obtuvo int",
+            " --> tests/logical_errors.fbr:2:10",
+            "error[E305]: El operador '||' requiere operandos bool, se obtuvo string",
+            " --> tests/logical_errors.fbr:3:19",
+        ]
+    );
+}
+
+#[test]
+fn for_loops_scope_their_control_variable() {
+    let (stdout, stderr) = run(&["tests/for.fbr"]);
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    assert!(stderr.is_empty());
+    assert_eq!(
+        lines,
+        vec![
+            "15", "7", "-1", "7", "0", "1", "10", "11", "20", "21", "0", "1", "99"
+        ]
+    );
+}
+
+#[test]
+fn break_and_continue_control_loops_and_switch() {
+    let (stdout, stderr) = run(&["tests/break_continue.fbr"]);
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    assert!(stderr.is_empty());
+    assert_eq!(
+        lines,
+        vec![
+            "1",
+            "2",
+            "4",
+            "5",
+            "0",
+            "4",
+            "6",
+            "0",
+            "fin de vuelta",
+            "dos",
+            "fin de vuelta",
+            "3",
+            "fin de vuelta",
+            "4",
+            "sin intentos",
+            "40",
+        ]
+    );
+}
+
+#[test]
+fn break_and_continue_outside_loops_are_rejected() {
+    let (stdout, stderr) = run(&["tests/break_errors.fbr"]);
+    let headers: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("error") || line.contains("-->"))
+        .collect();
+
+    assert!(stdout.is_empty());
+    assert_eq!(
+        headers,
+        vec![
+            "error[E205]: 'break' solo puede usarse dentro de un bucle o un switch",
+            " --> tests/break_errors.fbr:1:1",
+            "error[E205]: 'continue' solo puede usarse dentro de un bucle",
+            " --> tests/break_errors.fbr:3:1",
+            "error[E205]: 'break' solo puede usarse dentro de un bucle o un switch",
+            " --> tests/break_errors.fbr:6:9",
+            "error[E205]: 'continue' solo puede usarse dentro de un bucle",
+            "  --> tests/break_errors.fbr:13:9",
+        ]
+    );
+}
+
+#[test]
+fn runtime_errors_stop_execution_with_call_stack() {
+    for flags in [&["-n"][..], &["-n", "-b"][..]] {
+        let args = [flags, &["tests/runtime_trace.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+
+        assert_eq!(stdout, "antes\n", "{:?}", flags);
+        assert_eq!(code, Some(70), "{:?}", flags);
+        assert!(stderr.starts_with(
+            "error[E501]: Variable no definida: valores\n --> tests/runtime_trace.fbr:3:12\n"
+        ));
+        assert!(stderr.ends_with(
+            "  = note: en la función 'leer', llamada en 7:12\n  \
+             = note: en la función 'sumar', llamada en 12:13\n  \
+             = note: en la función 'principal', llamada en 16:1\n  \
+             = help: declare 'valores' con su tipo antes de usarla\n"
+        ));
+    }
+}
+
+#[test]
+fn exit_status_reflects_the_kind_of_failure() {
+    let (_, _, code) = run_with_status(&["tests/fib.fbr"], "");
+    assert_eq!(code, Some(0));
+
+    let (_, _, code) = run_with_status(&["tests/type_errors.fbr"], "");
+    assert_eq!(code, Some(65));
+
+    let (_, _, code) = run_with_status(&["tests/syntax_errors.fbr"], "");
+    assert_eq!(code, Some(65));
+
+    let (_, _, code) = run_with_status(&["tests/no_existe.fbr"], "");
+    assert_eq!(code, Some(64));
+}
+
+#[test]
+fn division_by_zero_is_reported_instead_of_crashing() {
+    for flags in [&[][..], &["-b"][..]] {
+        let args = [flags, &["tests/division_by_zero.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+
+        assert_eq!(stdout, "3\n2\n", "{:?}", flags);
+        assert_eq!(code, Some(70), "{:?}", flags);
+        assert!(stderr.starts_with("error[E504]: División por cero\n"));
+        assert!(!stderr.contains("panicked"));
+    }
+}
+
+#[test]
+fn missing_initializer_without_type_check_is_an_error() {
+    for flags in [&["-n"][..], &["-n", "-b"][..]] {
+        let args = [flags, &["tests/sin_inicializador.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+        assert_eq!((stdout.as_str(), code), ("1\n", Some(70)), "{:?}", flags);
+        assert!(stderr.starts_with(
+            "error[E515]: La variable 'x' necesita un valor inicial\n \
+             --> tests/sin_inicializador.fbr:3:1\n"
+        ));
+    }
+}
+
+#[test]
+fn unchecked_scripts_fail_without_panicking() {
+    let (tokens, _) = Lexer::new("int x;", &syntax()).tokenize();
+    let (ast, errors) = Parser::new(tokens).parse();
+    assert!(errors.is_empty());
+    let error = Interpreter::new(syntax()).interpret(ast).unwrap_err();
+    assert_eq!(error.kind.code(), "E515");
+
+    // Tokens de un anfitrión que no terminan en EOF
+    for source in ["int y = (", "int"] {
+        let (mut tokens, _) = Lexer::new(source, &syntax()).tokenize();
+        tokens.retain(|token| token.kind != Token::EOF);
+        let (_, errors) = Parser::new(tokens).parse();
+        assert_eq!(errors[0].message, MessageId::UnexpectedEndOfInput.into());
+    }
+}
+
+#[test]
+fn deep_recursion_runs_in_both_engines() {
+    for flags in [&["-n"][..], &["-n", "-b"][..]] {
+        let args = [flags, &["tests/recursion_profunda.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+
+        assert_eq!(stdout, "15000\n", "{:?}: {}", flags, stderr);
+        assert_eq!(code, Some(0), "{:?}", flags);
+    }
+}
+
+#[test]
+fn runaway_recursion_is_a_stack_overflow_error() {
+    for flags in [&["-n"][..], &["-n", "-b"][..]] {
+        let args = [flags, &["tests/recursion_sin_fin.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+
+        assert_eq!(stdout, "", "{:?}", flags);
+        assert_eq!(code, Some(70), "{:?}", flags);
+        assert!(stderr.starts_with(
+            "error[E509]: Desbordamiento de pila: se superó el límite de 20000 llamadas anidadas\n \
+             --> tests/recursion_sin_fin.fbr:2:12\n"
+        ));
+        // Solo se listan las llamadas más recientes
+        assert_eq!(stderr.matches("= note: en la función 'bajar'").count(), 10);
+        assert!(stderr.ends_with("  = note: ... y 19990 llamadas más\n"));
+    }
+}
+
+#[test]
+fn functions_must_return_on_every_path() {
+    let (stdout, stderr, code) = run_with_status(&["tests/sin_retorno.fbr"], "");
+    assert_eq!((stdout.as_str(), code), ("", Some(65)));
+    // Solo `parcial` puede llegar al final sin return
+    assert_eq!(stderr.matches("error[").count(), 1, "{}", stderr);
+    assert!(stderr.starts_with(
+        "error[E309]: La función 'parcial' puede terminar sin retornar un valor de tipo int\n"
+    ));
+}
+
+#[test]
+fn returned_values_are_checked_without_type_check() {
+    for flags in [&["-n"][..], &["-n", "-b"][..]] {
+        let args = [flags, &["tests/sin_retorno.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+        assert_eq!(
+            (stdout.as_str(), code),
+            ("24\n1\n", Some(70)),
+            "{:?}",
+            flags
+        );
+        assert!(stderr.starts_with(
+            "error[E518]: La función 'parcial' puede terminar sin retornar un valor de tipo int\n  \
+             --> tests/sin_retorno.fbr:26:1\n"
+        ));
+
+        let args = [flags, &["tests/retorno_tipo.fbr"]].concat();
+        let (stdout, stderr, code) = run_with_status(&args, "");
+        assert_eq!((stdout.as_str(), code), ("hola\n", Some(70)), "{:?}", flags);
+        assert!(
+            stderr.starts_with("error[E503]: "),
+            "{:?}: {}",
+            flags,
+            stderr
+        );
+        assert!(stderr.contains(" --> tests/retorno_tipo.fbr:2:5\n"));
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+use common::{fabriclang, output, scratch_dir};
+
+fn history_file(name: &str) -> PathBuf {
+    scratch_dir(name).join(".fabriclang_history")
+}
+
+fn repl(lines: &str, history: &PathBuf) -> (String, String) {
+    let (stdout, stderr, code) = output(fabriclang(&[]).env("FABRICLANG_HISTORY", history), lines);
+    assert_eq!(code, Some(0));
+    (stdout, stderr)
+}
+
+#[test]
+fn definitions_persist_and_expressions_are_echoed() {
+    let history = history_file("repl-state");
+    let (stdout, stderr) = repl(
+        "int x = 20;\n\
+         int function doble(int n) {\n\
+         return n * 2;\n\
+         }\n\
+         doble(x) + 2\n\
+         x = 1\n\
+         (x +\n\
+         2)\n\
+         println(\"hola\");\n",
+        &history,
+    );
+
+    assert_eq!(stderr, "");
+    assert!(stdout.starts_with("FabricLang 0.1.0"));
+    let session = stdout.split_once('\n').unwrap().1;
+    assert_eq!(
+        session,
+        ">>> >>> ... ... >>> 42\n>>> >>> ... 3\n>>> hola\n>>> \n"
+    );
+    fs::remove_dir_all(history.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn errors_do_not_end_the_session() {
+    let history = history_file("repl-errors");
+    let (stdout, stderr) = repl("y + 1\nint y = ;\nint y = 4;\ny + 1\n", &history);
+
+    assert!(stderr.starts_with("error[E301]: Variable no definida: y\n --> <repl>:1:1\n"));
+    assert!(stderr.contains("error[E203]: Se esperaba una expresión\n"));
+    assert!(stdout.ends_with(">>> 5\n>>> \n"));
+    fs::remove_dir_all(history.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn commands_inspect_and_reset_the_session() {
+    let history = history_file("repl-commands");
+    let (stdout, stderr) = repl(
+        "int x = 2;\n\
+         void function saludar(string nombre, int veces) { }\n\
+         :env\n\
+         :tokens x + 1\n\
+         :ast x\n\
+         :reset\n\
+         :env\n\
+         :desconocido\n\
+         :salir\n\
+         x\n",
+        &history,
+    );
+
+    assert!(stdout.contains(">>> int x = 2\nvoid saludar(string nombre, int veces)\n"));
+    assert!(stdout.contains("1:1\tIdentifier(\"x\")\n1:3\tPlus\n1:5\tIntegerLiteral(1)\n"));
+    assert!(stdout.contains("Expression {\n        expr: Variable {\n            name: \"x\","));
+    assert!(stdout.contains(">>> Entorno reiniciado\n>>> >>> >>> "));
+    assert!(stderr.contains("Comando desconocido: :desconocido"));
+    // `:salir` termina antes de evaluar la última línea
+    assert!(!stderr.contains("error["));
+
+    assert_eq!(
+        fs::read_to_string(&history).unwrap(),
+        "int x = 2;\n\
+         void function saludar(string nombre, int veces) { }\n\
+         :env\n\
+         :tokens x + 1\n\
+         :ast x\n\
+         :reset\n\
+         :env\n\
+         :desconocido\n\
+         :salir\n"
+    );
+    fs::remove_dir_all(history.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn entries_are_type_checked_before_running() {
+    let history = history_file("repl-types");
+    let (stdout, stderr) = repl(
+        "int x = \"a\";\n\
+         x\n\
+         int y;\n\
+         int function doble(int n) { return n * 2; }\n\
+         string s = doble(2);\n\
+         doble(3)\n",
+        &history,
+    );
+
+    assert!(
+        stderr.starts_with("error[E303]: Error de tipo: se esperaba int pero se recibió string\n")
+    );
+    // Lo que no pasa la verificación no queda declarado
+    assert!(stderr.contains("error[E301]: Variable no definida: x\n"));
+    assert!(stderr.contains("error[E307]: La variable 'y' necesita un valor inicial\n"));
+    assert_eq!(stderr.matches("error[").count(), 4, "{}", stderr);
+    assert!(stdout.ends_with(">>> 6\n>>> \n"));
+    fs::remove_dir_all(history.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn failed_entries_leave_no_declarations() {
+    let history = history_file("repl-runtime");
+    let (stdout, stderr) = repl("int x = 1/0;\nx\nint x = 2;\nx\n", &history);
+
+    assert!(stderr.starts_with("error[E504]: División por cero\n"));
+    // La verificación de tipos tampoco conserva `x`
+    assert!(stderr.contains("error[E301]: Variable no definida: x\n"));
+    assert_eq!(stderr.matches("error[").count(), 2, "{}", stderr);
+    assert!(stdout.ends_with(">>> 2\n>>> \n"));
+    fs::remove_dir_all(history.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn history_from_earlier_sessions_can_be_recalled() {
+    let history = history_file("repl-recall");
+    repl("int x = 2;\n(x +\n1)\n", &history);
+
+    let (stdout, stderr) = repl(":historial\n:repetir 1\n:repetir 2\n:repetir 9\n", &history);
+    assert!(stdout.contains("   1  int x = 2;\n   2  (x +\n      1)\n   3  :historial\n"));
+    assert!(stdout.ends_with(">>> >>> 3\n>>> >>> \n"), "{}", stdout);
+    assert_eq!(stderr, "No existe la entrada 9 del historial\n");
+    fs::remove_dir_all(history.parent().unwrap()).unwrap();
+}
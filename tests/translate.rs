@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::Path;
+
+use fabric_lang::core::lexer::Lexer;
+use fabric_lang::core::messages::{Arg, MessageId};
+use fabric_lang::core::translator::{TranslateError, translate};
+use fabric_lang::persistence::languages::find_language;
+use fabric_lang::persistence::loader::{DEFAULT_SYNTAX_CONFIG, parse_config};
+use fabric_lang::persistence::models::SyntaxConfig;
+
+mod common;
+
+use common::{fabriclang, output, scratch_dir};
+
+const SPANISH: &str = "src/config/lang/es/syntax.toml";
+
+fn english() -> SyntaxConfig {
+    parse_config(DEFAULT_SYNTAX_CONFIG, Path::new("syntax.toml")).unwrap()
+}
+
+fn spanish() -> SyntaxConfig {
+    find_language("es").unwrap().syntax().unwrap()
+}
+
+fn translated(
+    source: &str,
+    from: &SyntaxConfig,
+    to: &SyntaxConfig,
+) -> Result<String, Vec<TranslateError>> {
+    let (tokens, errors) = Lexer::new(source, from).tokenize();
+    assert!(errors.is_empty(), "{:?}", errors);
+    translate(source, &tokens, from, to)
+}
+
+#[test]
+fn only_dialect_words_are_rewritten() {
+    let source = "// si (x) mientras\nbool function par(int n) {\n\tif (n % 2 == 0) { return true; }   // fin\n    println(\"if true\");\n    return false;\n}\n";
+    assert_eq!(
+        translated(source, &english(), &spanish()).unwrap(),
+        "// si (x) mientras\nbooleano función par(entero n) {\n\tsi (n % 2 == 0) { retornar verdadero; }   // fin\n    escribir_linea(\"if true\");\n    retornar falso;\n}\n"
+    );
+}
+
+// Programas de prueba que usan como nombre una palabra reservada en español
+const REFUSED: &[(&str, &[&str])] = &[
+    ("ambitos.fbr", &["doble", "leer"]),
+    ("banco.fbr", &["continuar"]),
+    ("break_continue.fbr", &["doble"]),
+    ("runtime_trace.fbr", &["leer"]),
+    ("type_errors.fbr", &["doble"]),
+];
+
+#[test]
+fn programs_round_trip_through_another_dialect() {
+    let (english, spanish) = (english(), spanish());
+    let mut translated_programs = 0;
+    let mut refused_programs = 0;
+    for entry in fs::read_dir("tests").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "fbr") {
+            continue;
+        }
+        let source = fs::read_to_string(&path).unwrap();
+        if !Lexer::new(&source, &english).tokenize().1.is_empty() {
+            continue;
+        }
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        let refused = REFUSED
+            .iter()
+            .find(|(name, _)| *name == file_name)
+            .map(|(_, identifiers)| *identifiers);
+        let es = match (translated(&source, &english, &spanish), refused) {
+            (Ok(es), None) => es,
+            (Err(errors), Some(expected)) => {
+                let mut identifiers: Vec<&str> = errors
+                    .iter()
+                    .map(|e| match (e.message.id, &e.message.args[..]) {
+                        (MessageId::ReservedIdentifier, [Arg::Text(name), _]) => name.as_str(),
+                        _ => panic!("{}: error inesperado {:?}", path.display(), e),
+                    })
+                    .collect();
+                identifiers.sort();
+                identifiers.dedup();
+                assert_eq!(identifiers, expected, "{}", path.display());
+                refused_programs += 1;
+                continue;
+            }
+            (result, _) => panic!(
+                "{}: resultado inesperado {:?}",
+                path.display(),
+                result.map(|_| ())
+            ),
+        };
+        assert_ne!(es, source, "{}", path.display());
+        let back = translated(&es, &spanish, &english).unwrap();
+        assert_eq!(back, source, "{}", path.display());
+        translated_programs += 1;
+    }
+    assert!(translated_programs > 10, "{}", translated_programs);
+    assert_eq!(refused_programs, REFUSED.len());
+}
+
+#[test]
+fn identifiers_that_become_reserved_are_refused() {
+    let errors = translated("int si = 1;\nint x = si + 1;", &english(), &spanish()).unwrap_err();
+    let positions: Vec<(usize, usize, usize)> = errors
+        .iter()
+        .map(|e| (e.line, e.column, e.end_column))
+        .collect();
+    assert_eq!(positions, vec![(1, 5, 7), (2, 9, 11)]);
+    assert_eq!(
+        errors[0].message.to_string(),
+        "El identificador 'si' es una palabra reservada (keywords.If) en el dialecto de destino"
+    );
+}
+
+#[test]
+fn translated_program_runs_with_its_dialect() {
+    let dir = scratch_dir("translate");
+    let run = |args: &[&str]| output(&mut fabriclang(args), "");
+
+    let (program, _, code) = run(&[
+        "translate",
+        "--from",
+        "src/config/syntax.toml",
+        "--to",
+        SPANISH,
+        "tests/fib.fbr",
+    ]);
+    assert_eq!(code, Some(0));
+    let path = dir.join("fib.fbr");
+    fs::write(&path, program).unwrap();
+
+    let (stdout, _, _) = run(&["--syntax", SPANISH, path.to_str().unwrap()]);
+    assert_eq!(stdout, fs::read_to_string("tests/fib.out").unwrap());
+
+    let (_, stderr, code) = run(&[
+        "translate",
+        "--from",
+        "src/config/syntax.toml",
+        "--to",
+        SPANISH,
+        "tests/type_errors.fbr",
+    ]);
+    assert_eq!(code, Some(65));
+    assert!(stderr.contains("error[E601]"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,109 @@
+use std::fs;
+
+use fabric_lang::core::lexer::{Lexer, source_text};
+use fabric_lang::core::token::{Token, TokenData, Trivia, TriviaKind};
+
+mod common;
+
+use common::syntax;
+
+fn lossless(source: &str) -> Vec<TokenData> {
+    let mut lexer = Lexer::new(source, &syntax());
+    lexer.set_lossless(true);
+    lexer.tokenize().0
+}
+
+fn trivia(source: &str, trivia: &[Trivia]) -> Vec<(TriviaKind, String)> {
+    trivia
+        .iter()
+        .map(|t| (t.kind, source[t.start..t.end].to_string()))
+        .collect()
+}
+
+#[test]
+fn every_program_round_trips_byte_for_byte() {
+    let mut programs = 0;
+    for entry in fs::read_dir("tests").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "fbr") {
+            continue;
+        }
+        let source = fs::read_to_string(&path).unwrap();
+        let tokens = lossless(&source);
+        assert_eq!(source_text(&source, &tokens), source, "{}", path.display());
+
+        // La trivia no cambia los tokens que ve el parser
+        let (plain, _) = Lexer::new(&source, &syntax()).tokenize();
+        let kinds = |tokens: &[TokenData]| -> Vec<(Token, usize, usize)> {
+            tokens
+                .iter()
+                .map(|t| (t.kind.clone(), t.start, t.end))
+                .collect()
+        };
+        assert_eq!(kinds(&plain), kinds(&tokens), "{}", path.display());
+        assert!(
+            plain
+                .iter()
+                .all(|t| t.leading.is_empty() && t.trailing.is_empty())
+        );
+        programs += 1;
+    }
+    assert!(programs > 20, "{}", programs);
+}
+
+#[test]
+fn trailing_trivia_stops_at_the_end_of_the_line() {
+    let source = "int x = 1; // uno\n\n/* dos\n */ x = 2;  /* fin */ ";
+    let tokens = lossless(source);
+    let semi = &tokens[4];
+    assert_eq!(semi.kind, Token::Semi);
+    assert_eq!(
+        trivia(source, &semi.trailing),
+        vec![
+            (TriviaKind::Whitespace, " ".to_string()),
+            (TriviaKind::LineComment, "// uno".to_string()),
+        ]
+    );
+
+    let x = &tokens[5];
+    assert_eq!((x.start, x.end), (30, 31));
+    assert_eq!(
+        trivia(source, &x.leading),
+        vec![
+            (TriviaKind::Whitespace, "\n\n".to_string()),
+            (TriviaKind::BlockComment, "/* dos\n */".to_string()),
+            (TriviaKind::Whitespace, " ".to_string()),
+        ]
+    );
+
+    let last = &tokens[tokens.len() - 2];
+    assert_eq!(
+        trivia(source, &last.trailing),
+        vec![
+            (TriviaKind::Whitespace, "  ".to_string()),
+            (TriviaKind::BlockComment, "/* fin */".to_string()),
+            (TriviaKind::Whitespace, " ".to_string()),
+        ]
+    );
+    assert_eq!(tokens.last().unwrap().kind, Token::EOF);
+}
+
+#[test]
+fn unterminated_block_comment_is_a_lexical_error() {
+    let source = "int x = 1;\n  /* sin cerrar\nx = 2;";
+    let mut lexer = Lexer::new(source, &syntax());
+    lexer.set_lossless(true);
+    let (tokens, errors) = lexer.tokenize();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        (
+            errors[0].message.to_string().as_str(),
+            errors[0].line,
+            errors[0].column,
+            errors[0].end_column
+        ),
+        ("Unterminated block comment", 2, 3, 5)
+    );
+    assert_eq!(source_text(source, &tokens), source);
+}